@@ -4,7 +4,7 @@ use bevy::{
     prelude::*,
     window::{CursorGrabMode, PrimaryWindow},
 };
-use common::player::controller::PlayerInput;
+use common::player::{controller::PlayerInput, PLAYER_EYE_HEIGHT};
 
 use crate::player::LocalPlayer;
 
@@ -35,8 +35,6 @@ fn spawn_main_camera(mut commands: Commands) {
     ));
 }
 
-const CAMERA_HEIGHT_OFFSET: f32 = 0.8;
-
 fn move_camera_to_player(
     mut camera_q: Query<&mut Transform, With<MainCamera>>,
     player_q: Query<(&Position, &PlayerInput), With<LocalPlayer>>,
@@ -49,7 +47,7 @@ fn move_camera_to_player(
         return;
     };
 
-    camera_transform.translation = player_position + Vec3::Y * CAMERA_HEIGHT_OFFSET;
+    camera_transform.translation = player_position + Vec3::Y * PLAYER_EYE_HEIGHT;
     camera_transform.look_to(player_input.look_direction, Vec3::Y);
 }
 
@@ -0,0 +1,179 @@
+use avian3d::prelude::*;
+use bevy::{
+    color::palettes::css::{GRAY, GREEN},
+    prelude::*,
+};
+use common::{elements::door::*, GameLayer};
+
+use crate::{
+    entity_map::{
+        CleanupServerEntities, DespawnServerEntity, LocalServerEntity, ServerEntityMap,
+        ServerEntityMapper,
+    },
+    networking::prelude::*,
+    player::interaction::{Interactable, InteractionTarget},
+};
+
+use super::ModuleMessages;
+
+pub fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            spawn_doors,
+            receive_door_updates,
+            update_doors,
+            send_door_toggle_requests,
+        )
+            .chain(),
+    );
+
+    app.add_systems(PostUpdate, despawn_doors.in_set(CleanupServerEntities));
+}
+
+#[derive(Component)]
+pub struct Door {
+    open: bool,
+    interaction_collider_entity: Entity,
+}
+
+fn spawn_doors(
+    mut commands: Commands,
+    mut messages: MessageReceiver<NewDoor>,
+    mut mapper: ServerEntityMapper,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for NewDoor {
+        entity,
+        open,
+        translation,
+        rotation,
+    } in messages.drain()
+    {
+        let door_entity = mapper.get_or_spawn(entity);
+
+        // the door can be interacted with when open so it has a separate collider
+        let interaction_collider_entity = commands
+            .spawn((
+                door_collider(),
+                Position(translation),
+                Rotation(rotation),
+                CollisionLayers::new([GameLayer::Interaction], 0),
+                Interactable,
+                DebugRender::default(),
+            ))
+            .id();
+
+        commands.entity(door_entity).insert((
+            Door {
+                open,
+                interaction_collider_entity,
+            },
+            Mesh3d(meshes.add(Cuboid::from_size(DOOR_SIZE))),
+            MeshMaterial3d(materials.add(StandardMaterial::default())),
+            door_collider(),
+            Position(translation),
+            Rotation(rotation),
+            Transform {
+                translation,
+                rotation,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn receive_door_updates(
+    mut messages: MessageReceiver<UpdateDoor>,
+    map: Res<ServerEntityMap>,
+    mut door_q: Query<&mut Door>,
+) {
+    for UpdateDoor { entity, open, .. } in messages.drain() {
+        let Some(door_entity) = map.get_client_entity(entity) else {
+            warn!("Received door update for unknown entity {}", entity);
+            continue;
+        };
+
+        let Ok(mut door) = door_q.get_mut(door_entity) else {
+            error!("Couldn't query door {}", entity);
+            continue;
+        };
+
+        door.open = open;
+    }
+}
+
+/// Updates the collider and appearance of doors that opened or closed.
+fn update_doors(
+    mut commands: Commands,
+    door_q: Query<(Entity, &Door, &MeshMaterial3d<StandardMaterial>), Changed<Door>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (door_entity, door, material) in door_q.iter() {
+        commands
+            .entity(door_entity)
+            .insert(door_collision_layers(door.open));
+
+        let Some(material) = materials.get_mut(material) else {
+            continue;
+        };
+
+        material.base_color = if door.open {
+            GREEN.with_alpha(0.2).into()
+        } else {
+            GRAY.into()
+        };
+
+        material.alpha_mode = if door.open {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        };
+    }
+}
+
+fn send_door_toggle_requests(
+    input: Res<ButtonInput<MouseButton>>,
+    door_q: Query<(&Door, &LocalServerEntity)>,
+    interaction_target_q: Query<(), With<InteractionTarget>>,
+    mut messages: QueuedMessageSender<ModuleMessages>,
+    message_id: Res<MessageId<RequestToggleDoor>>,
+) {
+    let true = input.just_pressed(MouseButton::Left) else {
+        return;
+    };
+
+    for (door, door_server_entity) in door_q.iter() {
+        let true = interaction_target_q.contains(door.interaction_collider_entity) else {
+            continue;
+        };
+
+        messages.send(
+            *message_id,
+            RequestToggleDoor {
+                entity: door_server_entity.get(),
+            },
+        );
+
+        // Only one door can be the interaction target
+        return;
+    }
+}
+
+/// despawns the interaction colliders of doors that were despawned on the server
+fn despawn_doors(
+    mut commands: Commands,
+    mut despawn_r: EventReader<DespawnServerEntity>,
+    door_q: Query<&Door>,
+) {
+    for &DespawnServerEntity { client_entity } in despawn_r.read() {
+        let Ok(door) = door_q.get(client_entity) else {
+            continue;
+        };
+
+        commands
+            .entity(door.interaction_collider_entity)
+            .despawn_recursive();
+    }
+}
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 use crate::networking::prelude::MessageQueuePlugin;
 
+pub mod door;
 pub mod room_vent;
 pub mod ship_map;
 pub mod tank;
@@ -10,6 +11,7 @@ pub fn build(app: &mut App) {
     ship_map::build(app);
     tank::build(app);
     room_vent::build(app);
+    door::build(app);
 
     app.add_plugins(MessageQueuePlugin::<ModuleMessages>::default());
 }
@@ -7,7 +7,10 @@ use common::{elements::room_vent::*, GameLayer};
 
 use crate::{
     assets::GameAssets,
-    entity_map::{LocalServerEntity, ServerEntityMap, ServerEntityMapper},
+    entity_map::{
+        CleanupServerEntities, DespawnServerEntity, LocalServerEntity, ServerEntityMap,
+        ServerEntityMapper,
+    },
     networking::prelude::*,
     player::interaction::{Interactable, InteractionTarget},
     screens::{RenderLayerAllocater, Screens},
@@ -28,6 +31,8 @@ pub fn build(app: &mut App) {
             receive_room_vent_updates,
         ),
     );
+
+    app.add_systems(PostUpdate, despawn_room_vents.in_set(CleanupServerEntities));
 }
 
 #[derive(Component)]
@@ -232,7 +237,10 @@ fn receive_room_vent_updates(
     map: Res<ServerEntityMap>,
     mut room_vent_q: Query<&mut RoomVent>,
 ) {
-    for UpdateRoomVent { entity, enabled } in messages.drain() {
+    for UpdateRoomVent {
+        entity, enabled, ..
+    } in messages.drain()
+    {
         let Some(room_vent_entity) = map.get_client_entity(entity) else {
             warn!("Received room vent update for unknown entity {}", entity);
             continue;
@@ -246,3 +254,27 @@ fn receive_room_vent_updates(
         room_vent.enabled = enabled;
     }
 }
+
+/// despawns the helper entities of room vents that were despawned on the server
+///
+/// the screen entity is a child and is despawned with the room vent
+fn despawn_room_vents(
+    mut commands: Commands,
+    mut despawn_r: EventReader<DespawnServerEntity>,
+    room_vent_q: Query<&RoomVent>,
+) {
+    for &DespawnServerEntity { client_entity } in despawn_r.read() {
+        let Ok(room_vent) = room_vent_q.get(client_entity) else {
+            continue;
+        };
+
+        for entity in [
+            room_vent.screen_camera_entity,
+            room_vent.vent_ui_root_entity,
+            room_vent.enable_handle_mesh_entity,
+            room_vent.enable_handle_collider_entity,
+        ] {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
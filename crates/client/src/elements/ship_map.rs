@@ -8,7 +8,10 @@ use common::{
 };
 
 use crate::{
-    entity_map::{LocalServerEntity, ServerEntityMap, ServerEntityMapper},
+    entity_map::{
+        CleanupServerEntities, DespawnServerEntity, LocalServerEntity, ServerEntityMap,
+        ServerEntityMapper,
+    },
     networking::prelude::*,
     player::interaction::{Interactable, InteractionTarget},
     screens::*,
@@ -31,6 +34,8 @@ pub fn build(app: &mut App) {
             move_screen_camera,
         ),
     );
+
+    app.add_systems(PostUpdate, despawn_ship_maps.in_set(CleanupServerEntities));
 }
 
 #[derive(Resource)]
@@ -295,7 +300,10 @@ fn update_ship_maps(
     mut map_q: Query<(&Screen, &mut ScreenTargetPosition)>,
     mut camera_q: Query<&mut OrthographicProjection>,
 ) {
-    for ShipMapPositionUpdate { entity, position } in messages.drain() {
+    for ShipMapPositionUpdate {
+        entity, position, ..
+    } in messages.drain()
+    {
         let Some(map_entity) = entity_map.get_client_entity(entity) else {
             warn!(
                 "received a ship map position update for a non existent ship map {}",
@@ -355,3 +363,26 @@ fn move_screen_camera(
         transform.translation = (target_position.position + current_diff).extend(0.);
     }
 }
+
+/// despawns the keys and screen camera of ship maps that were despawned on the server
+fn despawn_ship_maps(
+    mut commands: Commands,
+    mut despawn_r: EventReader<DespawnServerEntity>,
+    map_q: Query<(&ShipMapKeys, &Screen)>,
+) {
+    for &DespawnServerEntity { client_entity } in despawn_r.read() {
+        let Ok((keys, screen)) = map_q.get(client_entity) else {
+            continue;
+        };
+
+        for entity in [
+            keys.left_key_entity,
+            keys.right_key_entity,
+            keys.up_key_entity,
+            keys.down_key_entity,
+            screen.camera_entity,
+        ] {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
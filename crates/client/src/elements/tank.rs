@@ -3,11 +3,14 @@ use bevy::{
     color::palettes::css::{GREEN, RED},
     prelude::*,
 };
-use common::{elements::tank::*, GameLayer};
+use common::{elements::tank::*, physics::SimulationTick, GameLayer};
 
 use crate::{
     assets::GameAssets,
-    entity_map::{LocalServerEntity, ServerEntityMap, ServerEntityMapper},
+    entity_map::{
+        CleanupServerEntities, DespawnServerEntity, LocalServerEntity, ServerEntityMap,
+        ServerEntityMapper,
+    },
     networking::prelude::*,
     player::interaction::{Interactable, InteractionTarget},
     screens::{RenderLayerAllocater, Screens},
@@ -29,6 +32,8 @@ pub fn build(app: &mut App) {
             receive_tank_percentage_updates,
         ),
     );
+
+    app.add_systems(PostUpdate, despawn_tanks.in_set(CleanupServerEntities));
 }
 
 #[derive(Component)]
@@ -40,6 +45,8 @@ pub struct Tank {
     enable_handle_mesh_entity: Entity,
     enable_handle_collider_entity: Entity,
     enabled: bool,
+    /// tick of the newest percentage update, older ones that arrive late are ignored
+    percentage_tick: Option<SimulationTick>,
 }
 
 const ENABLE_HANDLE_MESH_OFFSET: Vec3 = Vec3::new(0., -0.2, 0.);
@@ -161,6 +168,7 @@ fn spawn_tanks(
                 enable_handle_mesh_entity,
                 enable_handle_collider_entity,
                 enabled: state.enabled,
+                percentage_tick: None,
             },
             tank_transform,
         ));
@@ -172,7 +180,7 @@ fn update_tank_state(
     map: Res<ServerEntityMap>,
     mut tank_q: Query<&mut Tank>,
 ) {
-    for UpdateTankState { entity, state } in messages.drain() {
+    for UpdateTankState { entity, state, .. } in messages.drain() {
         let Some(tank_entity) = map.get_client_entity(entity) else {
             error!(
                 "Received tank state update for a tank that doesn't exist {}",
@@ -281,20 +289,34 @@ fn update_enabled_text(
 fn receive_tank_percentage_updates(
     mut messages: MessageReceiver<UpdateTankPercentage>,
     map: Res<ServerEntityMap>,
-    tank_q: Query<&Tank>,
+    mut tank_q: Query<&mut Tank>,
     mut text_q: Query<&mut Text>,
 ) {
-    for UpdateTankPercentage { entity, percentage } in messages.drain() {
+    for UpdateTankPercentage {
+        entity,
+        percentage,
+        tick,
+    } in messages.drain()
+    {
         let Some(tank_entity) = map.get_client_entity(entity) else {
             // percentage updates are unordered, it's ok if the tank doesn't exist
             continue;
         };
 
-        let Ok(tank) = tank_q.get(tank_entity) else {
+        let Ok(mut tank) = tank_q.get_mut(tank_entity) else {
             error!("Couldn't query tank {}", tank_entity);
             continue;
         };
 
+        if tank
+            .percentage_tick
+            .is_some_and(|percentage_tick| tick < percentage_tick)
+        {
+            continue;
+        }
+
+        tank.percentage_tick = Some(tick);
+
         let Ok(mut level_text) = text_q.get_mut(tank.level_text_entity) else {
             error!(
                 "Couldn't query tank {}'s level text {}",
@@ -306,3 +328,25 @@ fn receive_tank_percentage_updates(
         level_text.0 = format!("{:.0}%", percentage * 100.);
     }
 }
+
+/// despawns the helper entities of tanks that were despawned on the server
+fn despawn_tanks(
+    mut commands: Commands,
+    mut despawn_r: EventReader<DespawnServerEntity>,
+    tank_q: Query<&Tank>,
+) {
+    for &DespawnServerEntity { client_entity } in despawn_r.read() {
+        let Ok(tank) = tank_q.get(client_entity) else {
+            continue;
+        };
+
+        for entity in [
+            tank.screen_camera_entity,
+            tank.tank_ui_root,
+            tank.enable_handle_mesh_entity,
+            tank.enable_handle_collider_entity,
+        ] {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -2,14 +2,47 @@ use bevy::{
     ecs::{entity::EntityHashMap, system::SystemParam},
     prelude::*,
 };
-use common::ServerEntity;
+use common::{DespawnEntity, ServerEntity};
+
+use crate::networking::prelude::*;
 
 pub fn build(app: &mut App) {
     app.init_resource::<ServerEntityMap>();
 
-    app.add_systems(PostUpdate, remove_despawned_entities);
+    app.add_event::<DespawnServerEntity>();
+
+    app.configure_sets(
+        PostUpdate,
+        CleanupServerEntities.before(despawn_server_entities),
+    );
+
+    app.add_systems(Update, receive_despawn_messages);
+    app.add_systems(
+        OnEnter(ConnectionState::Disconnected),
+        despawn_all_server_entities,
+    );
+    app.add_systems(
+        PostUpdate,
+        (despawn_server_entities, remove_despawned_entities).chain(),
+    );
+}
+
+/// fired when the server despawns an entity that is mapped locally
+///
+/// the entity is despawned recursively during [PostUpdate],
+/// any helper entities that aren't children should be despawned
+/// by systems reading this event in [CleanupServerEntities]
+#[derive(Event)]
+pub struct DespawnServerEntity {
+    pub client_entity: Entity,
 }
 
+/// where helper entities of despawned server entities are cleaned up during [PostUpdate]
+///
+/// runs before the server entities themselves are despawned
+#[derive(Clone, Debug, PartialEq, Eq, Hash, SystemSet)]
+pub struct CleanupServerEntities;
+
 /// a two way map of [ServerEntities](ServerEntity) to local [Entities](Entity)
 ///
 /// you should use [ServerEntityMapper] to spawn server entities locally,
@@ -74,9 +107,9 @@ impl ServerEntityMap {
     ///
     /// returns the server entity if there was an entry
     pub fn remove_from_client(&mut self, client_entity: Entity) -> Option<ServerEntity> {
-        let server_entity = self.server_client.remove(&client_entity)?;
+        let server_entity = self.client_server.remove(&client_entity)?;
 
-        self.client_server
+        self.server_client
             .remove(&server_entity)
             .expect("Should have matching entry in reverse direction");
 
@@ -135,3 +168,46 @@ fn remove_despawned_entities(
         map.remove_from_client(client_entity);
     }
 }
+
+/// receives [DespawnEntity] messages and fires [DespawnServerEntity] events
+fn receive_despawn_messages(
+    mut messages: MessageReceiver<DespawnEntity>,
+    map: Res<ServerEntityMap>,
+    mut despawn_w: EventWriter<DespawnServerEntity>,
+) {
+    for DespawnEntity { entity } in messages.drain() {
+        let Some(client_entity) = map.get_client_entity(entity) else {
+            warn!(
+                "Received a despawn for an unmapped server entity {}",
+                entity
+            );
+            continue;
+        };
+
+        despawn_w.send(DespawnServerEntity { client_entity });
+    }
+}
+
+/// despawns local server entities that have been despawned on the server
+fn despawn_server_entities(
+    mut commands: Commands,
+    mut despawn_r: EventReader<DespawnServerEntity>,
+) {
+    for &DespawnServerEntity { client_entity } in despawn_r.read() {
+        if let Some(entity_commands) = commands.get_entity(client_entity) {
+            entity_commands.despawn_recursive();
+        }
+    }
+}
+
+/// despawns all local server entities when the connection to the server closes
+///
+/// the server will send them again if the client reconnects
+fn despawn_all_server_entities(
+    entity_q: Query<Entity, With<LocalServerEntity>>,
+    mut despawn_w: EventWriter<DespawnServerEntity>,
+) {
+    for client_entity in entity_q.iter() {
+        despawn_w.send(DespawnServerEntity { client_entity });
+    }
+}
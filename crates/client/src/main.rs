@@ -78,7 +78,11 @@ fn debug_join_server(mut connect_w: EventWriter<ConnectToServer>) {
             .parse()
             .expect("Bad server address format"),
         join_request: JoinRequest {
-            username: "Some Username".into(),
+            username: std::env::args()
+                .nth(2)
+                .unwrap_or_else(|| "Some Username".into()),
+            password: std::env::args().nth(3),
+            resume_token: None,
         },
     });
 }
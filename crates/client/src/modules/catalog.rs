@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use common::modules::{ShipModuleCatalog, ShipModuleCatalogEntry};
+
+use crate::{networking::prelude::*, state::ClientState};
+
+pub fn build(app: &mut App) {
+    app.init_resource::<ModuleCatalog>();
+
+    app.add_systems(OnEnter(ClientState::Disconnected), clear_module_catalog);
+    app.add_systems(Update, receive_module_catalog);
+}
+
+/// Every type of ship module on the server, received when joining.
+#[derive(Resource, Default)]
+pub struct ModuleCatalog {
+    pub module_types: Vec<ShipModuleCatalogEntry>,
+}
+
+/// the next server may have different module types
+fn clear_module_catalog(mut catalog: ResMut<ModuleCatalog>) {
+    catalog.module_types.clear();
+}
+
+fn receive_module_catalog(
+    mut messages: MessageReceiver<ShipModuleCatalog>,
+    mut catalog: ResMut<ModuleCatalog>,
+) {
+    for ShipModuleCatalog { module_types } in messages.drain() {
+        debug!("Received catalog of {} module types", module_types.len());
+
+        catalog.module_types = module_types;
+    }
+}
@@ -0,0 +1,251 @@
+use bevy::{color::palettes::css::AQUA, prelude::*};
+use common::modules::{
+    ModuleRotation, PlaceModuleRejected, RemoveModuleRejected, RequestPlaceModule,
+    RequestRemoveModule, ShipModuleTransform, SHIP_GRID_SCALE,
+};
+
+use crate::{camera::MainCamera, elements::ModuleMessages, networking::prelude::*, ui::UiElements};
+
+use super::catalog::ModuleCatalog;
+
+/// How far away from the camera modules can be placed.
+const BUILD_DISTANCE: f32 = 20.;
+
+pub fn build(app: &mut App) {
+    app.init_resource::<BuildMode>();
+
+    app.add_systems(
+        Update,
+        (
+            update_build_mode,
+            update_build_target,
+            draw_build_ghost,
+            send_place_module_requests,
+            send_remove_module_requests,
+            receive_place_module_rejections,
+            receive_remove_module_rejections,
+            update_build_mode_text,
+        )
+            .chain(),
+    );
+}
+
+/// State of the local player's build mode.
+#[derive(Resource, Default)]
+pub struct BuildMode {
+    pub enabled: bool,
+    /// index into the [ModuleCatalog]
+    pub selected: usize,
+    pub rotation: ModuleRotation,
+    /// where the selected module would be placed, if the camera is pointing at the ship grid
+    pub target: Option<IVec2>,
+    /// reason the last placement was rejected
+    pub rejection: Option<String>,
+}
+
+impl BuildMode {
+    fn target_transform(&self) -> Option<ShipModuleTransform> {
+        Some(ShipModuleTransform {
+            translation: self.target?,
+            rotation: self.rotation,
+        })
+    }
+}
+
+/// B toggles build mode, Q and E rotate the module and Tab cycles module types.
+fn update_build_mode(
+    input: Res<ButtonInput<KeyCode>>,
+    catalog: Res<ModuleCatalog>,
+    mut build_mode: ResMut<BuildMode>,
+) {
+    if input.just_pressed(KeyCode::KeyB) {
+        build_mode.enabled = !build_mode.enabled;
+        build_mode.rejection = None;
+    }
+
+    if !build_mode.enabled {
+        return;
+    }
+
+    if input.just_pressed(KeyCode::KeyQ) {
+        build_mode.rotation = build_mode.rotation.next_ccw();
+    }
+
+    if input.just_pressed(KeyCode::KeyE) {
+        build_mode.rotation = build_mode.rotation.next_cw();
+    }
+
+    if input.just_pressed(KeyCode::Tab) {
+        build_mode.selected += 1;
+    }
+
+    // the catalog is replaced when joining a server
+    if build_mode.selected >= catalog.module_types.len() {
+        build_mode.selected = 0;
+    }
+}
+
+/// Snaps the point on the ship grid that the camera is looking at to a grid space.
+fn update_build_target(
+    camera_q: Query<&Transform, With<MainCamera>>,
+    mut build_mode: ResMut<BuildMode>,
+) {
+    build_mode.target = None;
+
+    if !build_mode.enabled {
+        return;
+    }
+
+    let Ok(camera_transform) = camera_q.get_single() else {
+        return;
+    };
+
+    let origin = camera_transform.translation;
+    let direction = camera_transform.forward();
+
+    // the ship grid is on the y = 0 plane
+    let distance = -origin.y / direction.y;
+
+    if !distance.is_finite() || !(0. ..=BUILD_DISTANCE).contains(&distance) {
+        return;
+    }
+
+    let point = origin + direction * distance;
+
+    build_mode.target = Some(IVec2::new(
+        (point.x / SHIP_GRID_SCALE).round() as i32,
+        (point.z / SHIP_GRID_SCALE).round() as i32,
+    ));
+}
+
+/// Draws the grid spaces of the selected module where it would be placed.
+fn draw_build_ghost(build_mode: Res<BuildMode>, catalog: Res<ModuleCatalog>, mut gizmos: Gizmos) {
+    let Some(transform) = build_mode.target_transform() else {
+        return;
+    };
+
+    let Some(module_type) = catalog.module_types.get(build_mode.selected) else {
+        return;
+    };
+
+    for &space in module_type.grid_spaces.iter() {
+        let index = transform.translation + transform.rotation.rotate_index(space);
+
+        gizmos.cuboid(
+            Transform::from_xyz(
+                index.x as f32 * SHIP_GRID_SCALE,
+                0.,
+                index.y as f32 * SHIP_GRID_SCALE,
+            )
+            .with_scale(Vec3::new(SHIP_GRID_SCALE, 0.05, SHIP_GRID_SCALE)),
+            AQUA,
+        );
+    }
+}
+
+fn send_place_module_requests(
+    input: Res<ButtonInput<MouseButton>>,
+    mut build_mode: ResMut<BuildMode>,
+    catalog: Res<ModuleCatalog>,
+    mut messages: QueuedMessageSender<ModuleMessages>,
+    message_id: Res<MessageId<RequestPlaceModule>>,
+) {
+    let true = input.just_pressed(MouseButton::Left) else {
+        return;
+    };
+
+    let Some(transform) = build_mode.target_transform() else {
+        return;
+    };
+
+    let Some(module_type) = catalog.module_types.get(build_mode.selected) else {
+        return;
+    };
+
+    messages.send(
+        *message_id,
+        RequestPlaceModule {
+            module_type: module_type.module_name.clone(),
+            transform,
+        },
+    );
+
+    build_mode.rejection = None;
+}
+
+/// Right click removes the module at the build target.
+fn send_remove_module_requests(
+    input: Res<ButtonInput<MouseButton>>,
+    mut build_mode: ResMut<BuildMode>,
+    mut messages: QueuedMessageSender<ModuleMessages>,
+    message_id: Res<MessageId<RequestRemoveModule>>,
+) {
+    let true = input.just_pressed(MouseButton::Right) else {
+        return;
+    };
+
+    let Some(index) = build_mode.target else {
+        return;
+    };
+
+    messages.send(*message_id, RequestRemoveModule { index });
+
+    build_mode.rejection = None;
+}
+
+fn receive_place_module_rejections(
+    mut messages: MessageReceiver<PlaceModuleRejected>,
+    mut build_mode: ResMut<BuildMode>,
+) {
+    for PlaceModuleRejected { reason } in messages.drain() {
+        warn!("Module placement rejected: {}", reason);
+
+        build_mode.rejection = Some(reason.to_string());
+    }
+}
+
+fn receive_remove_module_rejections(
+    mut messages: MessageReceiver<RemoveModuleRejected>,
+    mut build_mode: ResMut<BuildMode>,
+) {
+    for RemoveModuleRejected { reason } in messages.drain() {
+        warn!("Module removal rejected: {}", reason);
+
+        build_mode.rejection = Some(reason.to_string());
+    }
+}
+
+fn update_build_mode_text(
+    build_mode: Res<BuildMode>,
+    catalog: Res<ModuleCatalog>,
+    ui_elements: Res<UiElements>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !build_mode.is_changed() && !catalog.is_changed() {
+        return;
+    }
+
+    let Ok(mut text) = text_q.get_mut(ui_elements.build_mode.status_text_entity) else {
+        return;
+    };
+
+    text.0 = if build_mode.enabled {
+        let module_name = catalog
+            .module_types
+            .get(build_mode.selected)
+            .map_or("nothing", |module_type| module_type.module_name.as_str());
+
+        let mut status = format!(
+            "Building {} facing {:?}\nTab: change module, Q/E: rotate, Right click: remove, B: exit",
+            module_name, build_mode.rotation
+        );
+
+        if let Some(rejection) = &build_mode.rejection {
+            status.push_str(&format!("\n{}", rejection));
+        }
+
+        status
+    } else {
+        String::new()
+    };
+}
@@ -1,9 +1,20 @@
 use bevy::prelude::*;
 
+use crate::entity_map::{CleanupServerEntities, DespawnServerEntity};
+
+pub mod catalog;
+pub mod construction;
 pub mod load;
 
 pub fn build(app: &mut App) {
+    catalog::build(app);
     load::build(app);
+    construction::build(app);
+
+    app.add_systems(
+        PostUpdate,
+        despawn_module_map_sprites.in_set(CleanupServerEntities),
+    );
 }
 
 /// points to a ship modules sprite entity with it's own transform
@@ -11,3 +22,18 @@ pub fn build(app: &mut App) {
 pub struct ModuleMapSprite {
     pub entity: Entity,
 }
+
+/// despawns the map sprites of modules that were despawned on the server
+fn despawn_module_map_sprites(
+    mut commands: Commands,
+    mut despawn_r: EventReader<DespawnServerEntity>,
+    module_q: Query<&ModuleMapSprite>,
+) {
+    for &DespawnServerEntity { client_entity } in despawn_r.read() {
+        let Ok(map_sprite) = module_q.get(client_entity) else {
+            continue;
+        };
+
+        commands.entity(map_sprite.entity).despawn_recursive();
+    }
+}
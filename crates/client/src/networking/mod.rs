@@ -6,14 +6,16 @@ use std::{
 use bevy::prelude::*;
 use common::networking::*;
 use nevy::prelude::*;
+use tls::{KnownServers, ServerVerifier, TlsSettings};
 
 pub mod message_queue;
 pub mod messages;
+pub mod tls;
 
 pub mod prelude {
     pub use super::message_queue::{MessageQueuePlugin, QueuedMessageSender};
     pub use super::messages::{MessageId, MessageReceiver, MessageSender};
-    pub use super::{ConnectToEndpoint, ConnectionState};
+    pub use super::{ConnectToEndpoint, ConnectionState, DisconnectFromEndpoint};
 }
 
 pub fn build(app: &mut App) {
@@ -24,14 +26,26 @@ pub fn build(app: &mut App) {
         server_client_protocol().build_deserialization(PreUpdate),
     ));
 
+    app.insert_resource(fingerprint::ProtocolFingerprint::compute());
+
+    let tls_settings = TlsSettings::default();
+    app.insert_resource(KnownServers::load(tls_settings.known_servers_path.clone()));
+    app.insert_resource(tls_settings);
+
     app.add_event::<ConnectToEndpoint>();
+    app.add_event::<DisconnectFromEndpoint>();
 
     app.init_state::<ConnectionState>();
 
     app.add_systems(Startup, spawn_endpoint);
     app.add_systems(
         Update,
-        (connect_to_simulators, on_connected, on_disconnected),
+        (
+            connect_to_simulators,
+            disconnect_from_endpoint,
+            on_connected,
+            on_disconnected,
+        ),
     );
 }
 
@@ -51,6 +65,12 @@ pub struct ConnectToEndpoint {
     pub addr: SocketAddr,
 }
 
+/// fire this event to close the [ServerConnection]
+///
+/// [ConnectionState] becomes [ConnectionState::Disconnected] once the connection has closed
+#[derive(Event)]
+pub struct DisconnectFromEndpoint;
+
 /// the connection state of the [ClientEndpoint]
 ///
 /// to connect to an endpoint fire a [ConnectToEndpoint] event
@@ -85,6 +105,8 @@ fn connect_to_simulators(
     mut next_state: ResMut<NextState<ConnectionState>>,
     endpoint_q: Query<Entity, With<ClientEndpoint>>,
     mut connections: Connections,
+    tls_settings: Res<TlsSettings>,
+    known_servers: Res<KnownServers>,
 ) {
     let endpoint_entity = endpoint_q.single();
 
@@ -101,13 +123,23 @@ fn connect_to_simulators(
             continue;
         }
 
+        // a configured CA that can't be loaded must not fall back to pinning
+        let connection_config = match create_connection_config(addr, &tls_settings, &known_servers)
+        {
+            Ok(connection_config) => connection_config,
+            Err(err) => {
+                error!("Refusing to connect to server {}: {}", addr, err);
+                continue;
+            }
+        };
+
         let Some(_) = connections
             .connect(
                 endpoint_entity,
                 Description::new_connect_description::<QuinnEndpoint>((
-                    create_connection_config(),
+                    connection_config,
                     addr,
-                    "dev.drewridley.com".into(),
+                    tls_settings.server_name.clone(),
                 )),
             )
             .expect("Description type should not mismatch")
@@ -123,73 +155,61 @@ fn connect_to_simulators(
     }
 }
 
-/// creates the quinn client config for a connection with a server
-///
-/// includes tls and transport config
-fn create_connection_config() -> nevy::quic::quinn_proto::ClientConfig {
-    // some day I need to figure out how to do tls properly
-    // someone help me
-
-    #[derive(Debug)]
-    struct AlwaysVerify;
-
-    impl rustls::client::danger::ServerCertVerifier for AlwaysVerify {
-        fn verify_server_cert(
-            &self,
-            _end_entity: &rustls::pki_types::CertificateDer<'_>,
-            _intermediates: &[rustls::pki_types::CertificateDer<'_>],
-            _server_name: &rustls::pki_types::ServerName<'_>,
-            _ocsp_response: &[u8],
-            _now: rustls::pki_types::UnixTime,
-        ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
-            Ok(rustls::client::danger::ServerCertVerified::assertion())
-        }
+/// system responsible for closing the connection with the server
+fn disconnect_from_endpoint(
+    mut disconnect_r: EventReader<DisconnectFromEndpoint>,
+    mut endpoint_q: Query<&mut BevyEndpoint, With<ClientEndpoint>>,
+    connection_q: Query<Entity, With<ServerConnection>>,
+) {
+    if disconnect_r.read().count() == 0 {
+        return;
+    }
 
-        fn verify_tls12_signature(
-            &self,
-            _message: &[u8],
-            _cert: &rustls::pki_types::CertificateDer<'_>,
-            _dss: &rustls::DigitallySignedStruct,
-        ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
-            Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
-        }
+    let Ok(connection_entity) = connection_q.get_single() else {
+        warn!("a DisconnectFromEndpoint event was fired when not connected");
+        return;
+    };
 
-        fn verify_tls13_signature(
-            &self,
-            _message: &[u8],
-            _cert: &rustls::pki_types::CertificateDer<'_>,
-            _dss: &rustls::DigitallySignedStruct,
-        ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
-            Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
-        }
+    let mut endpoint = endpoint_q.single_mut();
 
-        fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
-            vec![
-                rustls::SignatureScheme::RSA_PKCS1_SHA256,
-                rustls::SignatureScheme::RSA_PKCS1_SHA512,
-                rustls::SignatureScheme::RSA_PKCS1_SHA384,
-                rustls::SignatureScheme::RSA_PKCS1_SHA1,
-                rustls::SignatureScheme::RSA_PSS_SHA256,
-                rustls::SignatureScheme::RSA_PSS_SHA384,
-                rustls::SignatureScheme::RSA_PSS_SHA512,
-                rustls::SignatureScheme::ECDSA_NISTP256_SHA256,
-                rustls::SignatureScheme::ECDSA_NISTP521_SHA512,
-                rustls::SignatureScheme::ECDSA_NISTP384_SHA384,
-                rustls::SignatureScheme::ED25519,
-                rustls::SignatureScheme::ED448,
-                rustls::SignatureScheme::ECDSA_SHA1_Legacy,
-            ]
-        }
-    }
+    let Some(mut connection) = endpoint.connection_mut(connection_entity) else {
+        error!(
+            "server connection {:?} exists but the endpoint wouldn't return its state",
+            connection_entity
+        );
+        return;
+    };
+
+    info!("Disconnecting from server");
+
+    connection.disconnect();
+}
+
+/// creates the quinn client config for a connection with a server
+///
+/// includes tls and transport config
+///
+/// fails if the tls settings can't be used
+fn create_connection_config(
+    server_addr: SocketAddr,
+    tls_settings: &TlsSettings,
+    known_servers: &KnownServers,
+) -> Result<nevy::quic::quinn_proto::ClientConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let verifier = ServerVerifier::new(
+        server_addr,
+        known_servers.clone(),
+        tls::load_ca_verifier(tls_settings, &provider)?,
+        &provider,
+    );
 
-    let mut tls_config = rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(
-        rustls::crypto::ring::default_provider(),
-    ))
-    .with_protocol_versions(&[&rustls::version::TLS13])
-    .unwrap()
-    .dangerous()
-    .with_custom_certificate_verifier(Arc::new(AlwaysVerify))
-    .with_no_client_auth();
+    let mut tls_config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .unwrap()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
     tls_config.alpn_protocols = vec![b"h3".to_vec()];
 
     let quic_tls_config =
@@ -202,7 +222,7 @@ fn create_connection_config() -> nevy::quic::quinn_proto::ClientConfig {
     transport_config.keep_alive_interval(Some(std::time::Duration::from_millis(200)));
     quinn_client_config.transport_config(std::sync::Arc::new(transport_config));
 
-    quinn_client_config
+    Ok(quinn_client_config)
 }
 
 /// system responsible for changing state when the [ClientEndpoint] successfully makes a connection
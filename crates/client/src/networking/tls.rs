@@ -0,0 +1,250 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use common::networking::certificate_fingerprint;
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    crypto::{CryptoProvider, WebPkiSupportedAlgorithms},
+    pki_types::{pem::PemObject, CertificateDer, ServerName, UnixTime},
+    CertificateError, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+
+/// How the client decides to trust a server.
+///
+/// The default reads these environment variables:
+/// - `SHIP_GAME_SERVER_NAME` the name to verify the certificate against, defaults to "dev.shipgame"
+/// - `SHIP_GAME_CA` path to a pem file of CA certificates, when set servers must have a certificate signed by one of them
+/// - `SHIP_GAME_KNOWN_SERVERS` path to the known servers file, defaults to "known_servers"
+#[derive(Resource)]
+pub struct TlsSettings {
+    pub server_name: String,
+    pub ca_path: Option<PathBuf>,
+    pub known_servers_path: PathBuf,
+}
+
+impl Default for TlsSettings {
+    fn default() -> Self {
+        TlsSettings {
+            server_name: std::env::var("SHIP_GAME_SERVER_NAME")
+                .unwrap_or_else(|_| "dev.shipgame".into()),
+            ca_path: std::env::var_os("SHIP_GAME_CA").map(Into::into),
+            known_servers_path: std::env::var_os("SHIP_GAME_KNOWN_SERVERS")
+                .map(Into::into)
+                .unwrap_or_else(|| "known_servers".into()),
+        }
+    }
+}
+
+/// Certificate fingerprints of servers that have been trusted before.
+///
+/// Stored in a file with a line per server of the address followed by the sha256 of it's certificate.
+/// If a server's certificate legitimately changes it's line should be removed.
+#[derive(Resource, Clone)]
+pub struct KnownServers {
+    path: PathBuf,
+    servers: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl std::fmt::Debug for KnownServers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KnownServers")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl KnownServers {
+    pub fn load(path: PathBuf) -> Self {
+        let mut servers = HashMap::new();
+
+        match std::fs::read_to_string(&path) {
+            Ok(file) => {
+                for (line_index, line) in file.lines().enumerate() {
+                    let line = line.trim();
+
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+
+                    let Some((server, fingerprint)) = line.split_once(char::is_whitespace) else {
+                        warn!("Invalid line {} in {:?}", line_index + 1, path);
+                        continue;
+                    };
+
+                    servers.insert(server.to_string(), fingerprint.trim().to_string());
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+            Err(err) => error!("Couldn't read known servers {:?}: {}", path, err),
+        }
+
+        KnownServers {
+            path,
+            servers: Arc::new(Mutex::new(servers)),
+        }
+    }
+
+    /// Checks a server's certificate fingerprint, trusting it if the server is unknown.
+    fn verify(&self, server: &str, fingerprint: &str) -> bool {
+        let mut servers = self.servers.lock().unwrap();
+
+        if let Some(known_fingerprint) = servers.get(server) {
+            if known_fingerprint == fingerprint {
+                return true;
+            }
+
+            error!(
+                "The certificate of {} has changed from {} to {}, this server could be an impersonator. \
+                If the change is expected remove it's line from {:?}",
+                server, known_fingerprint, fingerprint, self.path
+            );
+
+            return false;
+        }
+
+        warn!(
+            "Trusting new server {} with certificate {}",
+            server, fingerprint
+        );
+
+        servers.insert(server.to_string(), fingerprint.to_string());
+
+        if let Err(err) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{} {}", server, fingerprint))
+        {
+            error!("Couldn't save known server to {:?}: {}", self.path, err);
+        }
+
+        true
+    }
+}
+
+/// Loads the CA certificates to verify servers against, if configured.
+///
+/// Returns an error if a CA is configured but can't be used,
+/// the client must not fall back to pinning in that case.
+pub fn load_ca_verifier(
+    settings: &TlsSettings,
+    provider: &Arc<CryptoProvider>,
+) -> Result<Option<Arc<WebPkiServerVerifier>>, String> {
+    let Some(ca_path) = settings.ca_path.as_ref() else {
+        return Ok(None);
+    };
+
+    let certificates = CertificateDer::pem_file_iter(ca_path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| format!("couldn't read CA certificates {:?}: {}", ca_path, err))?;
+
+    let mut roots = RootCertStore::empty();
+    let (added, ignored) = roots.add_parsable_certificates(certificates);
+
+    if ignored > 0 {
+        warn!(
+            "Ignored {} invalid CA certificates in {:?}",
+            ignored, ca_path
+        );
+    }
+
+    if added == 0 {
+        return Err(format!("no valid CA certificates in {:?}", ca_path));
+    }
+
+    WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map(Some)
+        .map_err(|err| format!("couldn't create CA verifier: {}", err))
+}
+
+/// Verifies a server's certificate against the configured CA,
+/// or pins it on first use if there isn't one.
+#[derive(Debug)]
+pub struct ServerVerifier {
+    server_addr: SocketAddr,
+    known_servers: KnownServers,
+    ca_verifier: Option<Arc<WebPkiServerVerifier>>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerVerifier {
+    pub fn new(
+        server_addr: SocketAddr,
+        known_servers: KnownServers,
+        ca_verifier: Option<Arc<WebPkiServerVerifier>>,
+        provider: &CryptoProvider,
+    ) -> Self {
+        ServerVerifier {
+            server_addr,
+            known_servers,
+            ca_verifier,
+            algorithms: provider.signature_verification_algorithms,
+        }
+    }
+}
+
+impl ServerCertVerifier for ServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Some(ca_verifier) = &self.ca_verifier {
+            return ca_verifier.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            );
+        }
+
+        let fingerprint = certificate_fingerprint(end_entity);
+
+        if self
+            .known_servers
+            .verify(&self.server_addr.to_string(), &fingerprint)
+        {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
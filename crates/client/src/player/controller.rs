@@ -1,39 +1,42 @@
 use avian3d::prelude::*;
 use bevy::{input::mouse::MouseMotion, prelude::*};
-use common::{player::*, GameLayer};
-use controller::PlayerInput;
+use common::player::{vitality::PlayerDead, *};
+use controller::{MovementMode, PlayerControllerSet, PlayerInput, PLAYER_MOVE_SPEED};
 
 use super::LocalPlayer;
 
-pub const PLAYER_MOVE_SPEED: f32 = 5.;
-pub const PLAYER_JUMP_SPEED: f32 = 3.;
-pub const ON_GROUND_TOLERANCE: f32 = 0.02;
-pub const RESET_FLOOR: f32 = -50.0;
-
 pub fn build(app: &mut App) {
-    controller::build_player_controller(app);
+    // when the server is authoritative the local player is stepped by prediction instead
+    controller::build_player_controller(app, PostUpdate);
+    app.configure_sets(
+        PostUpdate,
+        PlayerControllerSet.run_if(resource_equals(MovementMode::ClientAuthoritative)),
+    );
 
     app.insert_resource(MouseSensitivity(Vec2::splat(0.002)));
 
-    app.add_systems(
-        Update,
-        (
-            get_movement_input,
-            get_camera_input,
-            jump_players,
-            reset_fallen_player,
-        ),
-    );
+    app.add_systems(Update, (get_movement_input, get_camera_input));
 }
 
 fn get_movement_input(
     input: Res<ButtonInput<KeyCode>>,
-    mut player_q: Query<(&mut PlayerInput, &Rotation), With<LocalPlayer>>,
+    mut player_q: Query<(&mut PlayerInput, &Rotation, Has<PlayerDead>), With<LocalPlayer>>,
 ) {
-    let Ok((mut player_input, &Rotation(rotation))) = player_q.get_single_mut() else {
+    let Ok((mut player_input, &Rotation(rotation), dead)) = player_q.get_single_mut() else {
         return;
     };
 
+    if dead {
+        player_input.target_velocity = Vec2::ZERO;
+        player_input.jump = false;
+        return;
+    }
+
+    // cleared by the player controller once it has tried to jump
+    if input.just_pressed(KeyCode::Space) {
+        player_input.jump = true;
+    }
+
     let move_forward = input.pressed(KeyCode::KeyW);
     let move_backward = input.pressed(KeyCode::KeyS);
     let move_left = input.pressed(KeyCode::KeyA);
@@ -84,48 +87,3 @@ fn get_camera_input(
     )
     .unwrap();
 }
-
-fn jump_players(
-    input: Res<ButtonInput<KeyCode>>,
-    mut player_q: Query<(&Position, &mut LinearVelocity), With<LocalPlayer>>,
-    spatial_query: SpatialQuery,
-) {
-    let Ok((&Position(position), mut velocity)) = player_q.get_single_mut() else {
-        return;
-    };
-
-    let mut shape = player_collider();
-    shape.set_scale(Vec3::splat(0.99), 10);
-
-    let on_ground = spatial_query
-        .cast_shape(
-            &shape,
-            position,
-            Quat::IDENTITY,
-            Dir3::NEG_Y,
-            &ShapeCastConfig {
-                max_distance: ON_GROUND_TOLERANCE,
-                ignore_origin_penetration: true,
-                ..default()
-            },
-            &SpatialQueryFilter::from_mask([GameLayer::World]),
-        )
-        .is_some();
-
-    if on_ground && input.just_pressed(KeyCode::Space) {
-        velocity.0 += Vec3::Y * PLAYER_JUMP_SPEED;
-    }
-}
-
-fn reset_fallen_player(
-    mut player_q: Query<(&mut Position, &mut LinearVelocity), With<LocalPlayer>>,
-) {
-    let Ok((mut position, mut linear_velocity)) = player_q.get_single_mut() else {
-        return;
-    };
-
-    if position.0.y < RESET_FLOOR {
-        position.0 = Vec3::new(0., 1., 0.);
-        *linear_velocity = LinearVelocity::default();
-    }
-}
@@ -1,10 +1,8 @@
 use avian3d::prelude::*;
 use bevy::{color::palettes::css::*, prelude::*};
-use common::GameLayer;
+use common::{player::INTERACTION_DISTANCE, GameLayer};
 
-use crate::camera::MainCamera;
-
-pub const INTERACTION_DISTANCE: f32 = 2.;
+use crate::{camera::MainCamera, modules::construction::BuildMode};
 
 pub fn build(app: &mut App) {
     app.add_systems(Update, (set_interaction_target, debug_interaction).chain());
@@ -17,6 +15,8 @@ pub fn build(app: &mut App) {
 pub struct Interactable;
 
 /// Inserted onto [Interactable] entities that the player camera is interacting with
+///
+/// Nothing is targeted in build mode, so that building doesn't also interact with elements.
 #[derive(Component)]
 pub struct InteractionTarget;
 
@@ -26,11 +26,16 @@ fn set_interaction_target(
     interactable_q: Query<(), With<Interactable>>,
     camera_q: Query<&Transform, With<MainCamera>>,
     spatial_query: SpatialQuery,
+    build_mode: Res<BuildMode>,
 ) {
     for target_entity in target_q.iter() {
         commands.entity(target_entity).remove::<InteractionTarget>();
     }
 
+    if build_mode.enabled {
+        return;
+    }
+
     let Ok(camera_transform) = camera_q.get_single() else {
         return;
     };
@@ -10,12 +10,14 @@ use crate::physics::playout::SnapshotInterpolation;
 pub mod controller;
 pub mod interaction;
 pub mod networking;
+pub mod prediction;
 pub mod vitality;
 
 pub fn build(app: &mut App) {
     networking::build(app);
     controller::build(app);
     interaction::build(app);
+    prediction::build(app);
     vitality::build(app);
 }
 
@@ -3,14 +3,27 @@ use std::time::Duration;
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use common::player::*;
-use controller::PlayerInput;
+use controller::{MovementMode, PlayerInput};
+use vitality::PlayerDead;
 
-use crate::{entity_map::ServerEntityMapper, networking::prelude::*};
+use crate::{
+    entity_map::{ServerEntityMap, ServerEntityMapper},
+    networking::prelude::*,
+};
 
-use super::{LocalPlayer, LocalPlayerBundle, PlayerBundle};
+use super::{LocalPlayer, LocalPlayerBundle, Player, PlayerBundle};
 
 pub fn build(app: &mut App) {
-    app.add_systems(Update, (create_new_players, send_state_updates));
+    app.add_systems(
+        Update,
+        (
+            create_new_players,
+            log_players_leaving,
+            receive_player_respawns,
+            receive_player_corrections,
+            send_state_updates.run_if(resource_equals(MovementMode::ClientAuthoritative)),
+        ),
+    );
 }
 
 fn create_new_players(
@@ -38,6 +51,76 @@ fn create_new_players(
     }
 }
 
+/// the player entity itself is despawned by [DespawnEntity](common::DespawnEntity)
+fn log_players_leaving(
+    mut messages: MessageReceiver<PlayerLeft>,
+    map: Res<ServerEntityMap>,
+    player_q: Query<&Player>,
+) {
+    for PlayerLeft { server_entity } in messages.drain() {
+        let Some(player) = map
+            .get_client_entity(server_entity)
+            .and_then(|player_entity| player_q.get(player_entity).ok())
+        else {
+            continue;
+        };
+
+        info!("player \"{}\" left", player.username);
+    }
+}
+
+/// Revives respawned players and moves the local player to where it respawned.
+fn receive_player_respawns(
+    mut commands: Commands,
+    mut messages: MessageReceiver<PlayerRespawned>,
+    map: Res<ServerEntityMap>,
+    mut local_player_q: Query<(&mut Position, &mut LinearVelocity), With<LocalPlayer>>,
+) {
+    for PlayerRespawned {
+        server_entity,
+        position,
+    } in messages.drain()
+    {
+        let Some(player_entity) = map.get_client_entity(server_entity) else {
+            warn!("Received respawn of unknown player {}", server_entity);
+            continue;
+        };
+
+        commands.entity(player_entity).remove::<PlayerDead>();
+
+        if let Ok((mut player_position, mut player_velocity)) =
+            local_player_q.get_mut(player_entity)
+        {
+            player_position.0 = position;
+            player_velocity.0 = Vec3::ZERO;
+        }
+    }
+}
+
+/// Moves the local player back to where the server says it is after an update was rejected.
+fn receive_player_corrections(
+    mut messages: MessageReceiver<PlayerCorrection>,
+    mut local_player_q: Query<(&mut Position, &mut LinearVelocity), With<LocalPlayer>>,
+) {
+    for PlayerCorrection {
+        position,
+        linear_velocity,
+    } in messages.drain()
+    {
+        let Ok((mut player_position, mut player_velocity)) = local_player_q.get_single_mut() else {
+            continue;
+        };
+
+        warn!(
+            "Server corrected local player from {} to {}",
+            player_position.0, position
+        );
+
+        player_position.0 = position;
+        player_velocity.0 = linear_velocity;
+    }
+}
+
 const PLAYER_STATE_UPDATE_INTERVAL: Duration = Duration::from_millis(150);
 
 fn send_state_updates(
@@ -59,6 +142,7 @@ fn send_state_updates(
         messages.send(
             *message_id,
             &ClientPlayerUpdate {
+                time: time.elapsed(),
                 position,
                 linear_velocity,
                 input,
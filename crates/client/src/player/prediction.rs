@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+use avian3d::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use common::player::{
+    controller::{build_player_controller, MovementMode, PlayerInput},
+    ClientPlayerInput, PlayerInputAck,
+};
+
+use crate::{networking::prelude::*, state::ClientState};
+
+use super::LocalPlayer;
+
+/// How far the server's state can be from the predicted state before replaying inputs.
+const RECONCILE_TOLERANCE: f32 = 0.01;
+/// Inputs the server hasn't acknowledged beyond this are forgotten, oldest first.
+const MAX_PREDICTED_INPUTS: usize = 256;
+
+pub fn build(app: &mut App) {
+    app.init_resource::<MovementMode>();
+    app.init_resource::<PredictionHistory>();
+
+    app.init_schedule(PredictionStep);
+    app.configure_sets(
+        PredictionStep,
+        (PhysicsSet::Prepare, PhysicsSet::StepSimulation).chain(),
+    );
+    build_player_controller(app, PredictionStep);
+
+    app.add_systems(OnEnter(ClientState::Ingame), reset_prediction_history);
+    app.add_systems(OnEnter(ClientState::Disconnected), reset_movement_mode);
+    app.add_systems(Update, receive_input_acks);
+    app.add_systems(
+        FixedUpdate,
+        (
+            reconcile_local_player,
+            send_player_input,
+            predict_local_player,
+        )
+            .chain()
+            .run_if(resource_equals(MovementMode::ServerAuthoritative)),
+    );
+}
+
+/// Schedule that steps the local player by one tick of it's [PlayerInput].
+///
+/// Runs once every fixed tick when the [MovementMode] is server authoritative,
+/// and again for every input replayed when reconciling with the server.
+#[derive(ScheduleLabel, Hash, PartialEq, Eq, Debug, Clone)]
+pub struct PredictionStep;
+
+/// Inputs sent to the server that it hasn't acknowledged yet,
+/// with the state the local player was predicted to be in after each one.
+#[derive(Resource, Default)]
+struct PredictionHistory {
+    next_sequence: u32,
+    inputs: VecDeque<PredictedInput>,
+    /// newest acknowledgement from the server that hasn't been reconciled
+    latest_ack: Option<PlayerInputAck>,
+}
+
+struct PredictedInput {
+    sequence: u32,
+    input: PlayerInput,
+    position: Vec3,
+    linear_velocity: Vec3,
+}
+
+/// sequence numbers start again for every session
+fn reset_prediction_history(mut history: ResMut<PredictionHistory>) {
+    *history = PredictionHistory::default();
+}
+
+/// the next server sends it's own movement mode and tick rate when joining
+fn reset_movement_mode(mut commands: Commands) {
+    commands.insert_resource(MovementMode::default());
+    commands.insert_resource(Time::<Fixed>::default());
+}
+
+fn receive_input_acks(
+    mut messages: MessageReceiver<PlayerInputAck>,
+    mut history: ResMut<PredictionHistory>,
+) {
+    for ack in messages.drain() {
+        // acks are sent unordered, late ones are dropped
+        if history
+            .latest_ack
+            .is_none_or(|latest_ack| ack.sequence > latest_ack.sequence)
+        {
+            history.latest_ack = Some(ack);
+        }
+    }
+}
+
+fn local_player_state(world: &mut World) -> Option<(Entity, Vec3, Vec3)> {
+    world
+        .query_filtered::<(Entity, &Position, &LinearVelocity), With<LocalPlayer>>()
+        .get_single(world)
+        .ok()
+        .map(|(player_entity, position, linear_velocity)| {
+            (player_entity, position.0, linear_velocity.0)
+        })
+}
+
+/// Compares the newest [PlayerInputAck] with the state predicted for that input.
+///
+/// If they disagree the local player is moved back to the server's state
+/// and every input after it is replayed.
+fn reconcile_local_player(world: &mut World) {
+    let Some(ack) = world.resource_mut::<PredictionHistory>().latest_ack.take() else {
+        return;
+    };
+
+    let Some((player_entity, ..)) = local_player_state(world) else {
+        return;
+    };
+
+    let mut history = world.resource_mut::<PredictionHistory>();
+
+    // the server has already simulated these inputs
+    while history
+        .inputs
+        .front()
+        .is_some_and(|predicted| predicted.sequence < ack.sequence)
+    {
+        history.inputs.pop_front();
+    }
+
+    if history
+        .inputs
+        .front()
+        .is_none_or(|predicted| predicted.sequence != ack.sequence)
+    {
+        return;
+    }
+
+    let Some(predicted) = history.inputs.pop_front() else {
+        return;
+    };
+
+    if predicted.position.distance(ack.position) < RECONCILE_TOLERANCE
+        && predicted.linear_velocity.distance(ack.linear_velocity) < RECONCILE_TOLERANCE
+    {
+        return;
+    }
+
+    debug!(
+        "Reconciling local player with input {}, predicted {} but server has {}",
+        ack.sequence, predicted.position, ack.position
+    );
+
+    let replay_count = history.inputs.len();
+
+    let mut player = world.entity_mut(player_entity);
+    let Some(&current_input) = player.get::<PlayerInput>() else {
+        return;
+    };
+
+    player.insert((Position(ack.position), LinearVelocity(ack.linear_velocity)));
+
+    for index in 0..replay_count {
+        let input = world.resource::<PredictionHistory>().inputs[index].input;
+        world.entity_mut(player_entity).insert(input);
+
+        world.run_schedule(PredictionStep);
+
+        let Some((_, position, linear_velocity)) = local_player_state(world) else {
+            return;
+        };
+
+        let predicted = &mut world.resource_mut::<PredictionHistory>().inputs[index];
+        predicted.position = position;
+        predicted.linear_velocity = linear_velocity;
+    }
+
+    world.entity_mut(player_entity).insert(current_input);
+}
+
+/// Sends this tick's input to the server and adds it to the [PredictionHistory].
+fn send_player_input(
+    player_q: Query<(&PlayerInput, &Position, &LinearVelocity), With<LocalPlayer>>,
+    mut history: ResMut<PredictionHistory>,
+    mut messages: MessageSender,
+    message_id: Res<MessageId<ClientPlayerInput>>,
+) {
+    let Ok((&input, &Position(position), &LinearVelocity(linear_velocity))) = player_q.get_single()
+    else {
+        return;
+    };
+
+    let sequence = history.next_sequence;
+    history.next_sequence += 1;
+
+    messages.send(*message_id, &ClientPlayerInput { sequence, input });
+
+    // the predicted state is filled in by predict_local_player
+    history.inputs.push_back(PredictedInput {
+        sequence,
+        input,
+        position,
+        linear_velocity,
+    });
+
+    if history.inputs.len() > MAX_PREDICTED_INPUTS {
+        history.inputs.pop_front();
+    }
+}
+
+/// Steps the local player with this tick's input and records the predicted state.
+fn predict_local_player(world: &mut World) {
+    if local_player_state(world).is_none() {
+        return;
+    }
+
+    world.run_schedule(PredictionStep);
+
+    let Some((_, position, linear_velocity)) = local_player_state(world) else {
+        return;
+    };
+
+    if let Some(predicted) = world.resource_mut::<PredictionHistory>().inputs.back_mut() {
+        predicted.position = position;
+        predicted.linear_velocity = linear_velocity;
+    }
+}
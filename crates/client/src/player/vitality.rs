@@ -1,19 +1,26 @@
 use bevy::prelude::*;
 use common::player::vitality::*;
 
-use crate::{networking::prelude::*, ui::UiElements};
+use crate::{entity_map::ServerEntityMap, networking::prelude::*, ui::UiElements};
 
 use super::LocalPlayer;
 
 pub fn build(app: &mut App) {
-    app.add_systems(Update, (update_health_ui, receive_player_vitality_updates));
+    app.add_systems(
+        Update,
+        (
+            update_health_ui,
+            receive_player_vitality_updates,
+            receive_player_deaths,
+        ),
+    );
 }
 
 fn receive_player_vitality_updates(
     mut messages: MessageReceiver<UpdatePlayerVitality>,
     mut player_vitality: Query<&mut PlayerVitality, With<LocalPlayer>>,
 ) {
-    for UpdatePlayerVitality { vitality } in messages.drain() {
+    for UpdatePlayerVitality { vitality, .. } in messages.drain() {
         let Ok(mut player_vitality) = player_vitality.get_single_mut() else {
             continue;
         };
@@ -22,8 +29,29 @@ fn receive_player_vitality_updates(
     }
 }
 
+fn receive_player_deaths(
+    mut commands: Commands,
+    mut messages: MessageReceiver<PlayerDied>,
+    map: Res<ServerEntityMap>,
+) {
+    for PlayerDied {
+        server_entity,
+        cause,
+    } in messages.drain()
+    {
+        let Some(player_entity) = map.get_client_entity(server_entity) else {
+            warn!("Received death of unknown player {}", server_entity);
+            continue;
+        };
+
+        info!("Player {} died: {}", player_entity, cause);
+
+        commands.entity(player_entity).insert(PlayerDead { cause });
+    }
+}
+
 fn update_health_ui(
-    local_player_q: Query<&PlayerVitality, With<LocalPlayer>>,
+    local_player_q: Query<(&PlayerVitality, Option<&PlayerDead>), With<LocalPlayer>>,
     ui_elements: Res<UiElements>,
     mut visibility_q: Query<&mut Visibility>,
     mut text_q: Query<&mut Text>,
@@ -33,16 +61,19 @@ fn update_health_ui(
         return;
     };
 
-    let Ok([mut pressure_text, mut health_text, mut oxygen_text]) = text_q.get_many_mut([
-        ui_elements.vitality.pressure_level_entity,
-        ui_elements.vitality.health_level_entity,
-        ui_elements.vitality.oxygen_level_entity,
-    ]) else {
+    let Ok([mut pressure_text, mut health_text, mut oxygen_text, mut death_text]) = text_q
+        .get_many_mut([
+            ui_elements.vitality.pressure_level_entity,
+            ui_elements.vitality.health_level_entity,
+            ui_elements.vitality.oxygen_level_entity,
+            ui_elements.vitality.death_text_entity,
+        ])
+    else {
         error!("Couldn't query player health text");
         return;
     };
 
-    let Ok(player_health) = local_player_q.get_single() else {
+    let Ok((player_health, dead)) = local_player_q.get_single() else {
         *visibility = Visibility::Hidden;
         return;
     };
@@ -52,4 +83,8 @@ fn update_health_ui(
     pressure_text.0 = format!("Pressure {:.0}%", player_health.pressure * 100.);
     health_text.0 = format!("Health {:.0}", player_health.health);
     oxygen_text.0 = format!("Oxygen {:.0}", player_health.oxygen);
+    death_text.0 = match dead {
+        Some(PlayerDead { cause }) => format!("{}", cause),
+        None => String::new(),
+    };
 }
@@ -1,20 +1,37 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
-use bevy::prelude::*;
-use common::state::*;
+use bevy::{prelude::*, utils::HashMap};
+use common::{
+    networking::{fingerprint::ProtocolFingerprint, ProtocolHandshake, ProtocolRejected},
+    state::*,
+};
 
-use crate::networking::prelude::*;
+use crate::{networking::prelude::*, ui::UiElements};
 
 pub fn build(app: &mut App) {
     app.init_state::<ClientState>();
 
+    app.init_resource::<ResumeTokens>();
+
     app.add_event::<ConnectToServer>();
     app.add_event::<DisconnectFromServer>();
 
     app.add_plugins(MessageQueuePlugin::<JoinRequestQueue>::default());
 
-    app.add_systems(Update, connect_to_server);
+    app.add_systems(
+        Update,
+        (
+            connect_to_server,
+            disconnect_from_server,
+            receive_protocol_rejections,
+            receive_join_responses,
+            receive_join_rejections,
+            reconnect.run_if(resource_exists::<Reconnect>),
+            update_connection_ui,
+        ),
+    );
     app.add_systems(OnEnter(ConnectionState::Connected), send_join_request);
+    app.add_systems(OnEnter(ConnectionState::Disconnected), on_connection_lost);
 }
 
 /// fire this event to connect to and join a servers game
@@ -30,18 +47,61 @@ pub struct ConnectToServer {
 #[derive(Event)]
 pub struct DisconnectFromServer;
 
-/// holds the join request whilst waiting for connection to server
+/// holds the join request for the server that is being connected to or is connected
 ///
-/// only exists in the [ClientState::Connecting] state
+/// kept after joining so that the client can reconnect
 #[derive(Resource)]
 struct CurrentJoinRequest {
+    server_addr: SocketAddr,
     join_request: JoinRequest,
 }
 
+/// why the client last returned to [ClientState::Disconnected]
+///
+/// removed when connecting to a server
+#[derive(Resource, Debug)]
+pub enum DisconnectReason {
+    /// the server rejected the join request
+    Rejected(JoinRejectReason),
+    /// the connection to the server closed
+    ConnectionLost,
+}
+
+impl std::fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisconnectReason::Rejected(reason) => write!(f, "{}", reason),
+            DisconnectReason::ConnectionLost => write!(f, "Lost connection to the server"),
+        }
+    }
+}
+
+/// the [ResumeToken]s issued by servers this session
+#[derive(Resource, Default)]
+pub struct ResumeTokens {
+    tokens: HashMap<SocketAddr, ResumeToken>,
+}
+
+impl ResumeTokens {
+    pub fn get(&self, server_addr: SocketAddr) -> Option<ResumeToken> {
+        self.tokens.get(&server_addr).copied()
+    }
+}
+
+/// how long to wait before trying to reconnect after losing connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// exists when the client will try to reconnect to the last server
+#[derive(Resource)]
+struct Reconnect {
+    timer: Timer,
+}
+
 /// system responsible for starting the connection with the server
 fn connect_to_server(
     mut commands: Commands,
     client_state: Res<State<ClientState>>,
+    connection_state: Res<State<ConnectionState>>,
     mut next_client_state: ResMut<NextState<ClientState>>,
     mut connect_r: EventReader<ConnectToServer>,
     mut connect_w: EventWriter<ConnectToEndpoint>,
@@ -56,31 +116,241 @@ fn connect_to_server(
             error!("ConnectToServer event was fired whilst not disconnected");
             continue;
         }
+
+        // the previous connection may still be closing
+        if !matches!(connection_state.get(), ConnectionState::Disconnected) {
+            error!("ConnectToServer event was fired before the last connection closed");
+            continue;
+        }
         connecting = true;
         next_client_state.set(ClientState::Connecting);
 
         connect_w.send(ConnectToEndpoint { addr: server_addr });
         commands.insert_resource(CurrentJoinRequest {
+            server_addr,
             join_request: join_request.clone(),
         });
+        commands.remove_resource::<Reconnect>();
+        commands.remove_resource::<DisconnectReason>();
+    }
+}
+
+/// system responsible for leaving the current server when [DisconnectFromServer] is fired
+///
+/// the client won't try to reconnect
+fn disconnect_from_server(
+    mut commands: Commands,
+    mut disconnect_r: EventReader<DisconnectFromServer>,
+    client_state: Res<State<ClientState>>,
+    mut next_client_state: ResMut<NextState<ClientState>>,
+    mut disconnect_w: EventWriter<DisconnectFromEndpoint>,
+) {
+    if disconnect_r.read().count() == 0 {
+        return;
+    }
+
+    if let ClientState::Disconnected = client_state.get() {
+        warn!("DisconnectFromServer event was fired whilst disconnected");
+        return;
     }
+
+    next_client_state.set(ClientState::Disconnected);
+    commands.remove_resource::<Reconnect>();
+    disconnect_w.send(DisconnectFromEndpoint);
 }
 
 /// marker for join request message queue
 struct JoinRequestQueue;
 
-/// system responsible for sending the join request
+/// system responsible for sending the join request and entering [ClientState::Joining]
+///
+/// the [ProtocolHandshake] is sent first, the server won't accept the request without it
+///
+/// includes the [ResumeToken] for the server if there is one
 fn send_join_request(
-    mut commands: Commands,
+    mut next_client_state: ResMut<NextState<ClientState>>,
     join_request: Res<CurrentJoinRequest>,
+    resume_tokens: Res<ResumeTokens>,
+    fingerprint: Res<ProtocolFingerprint>,
     mut messages: QueuedMessageSender<JoinRequestQueue>,
+    handshake_message_id: Res<MessageId<ProtocolHandshake>>,
     message_id: Res<MessageId<JoinRequest>>,
 ) {
-    commands.remove_resource::<CurrentJoinRequest>();
+    messages.send(
+        *handshake_message_id,
+        ProtocolHandshake {
+            fingerprint: *fingerprint,
+        },
+    );
+
+    let mut request = join_request.join_request.clone();
+
+    if let Some(resume_token) = resume_tokens.get(join_request.server_addr) {
+        request.resume_token = Some(resume_token);
+    }
+
+    info!("joining server: {}", request.username);
+
+    messages.send(*message_id, request);
+
+    next_client_state.set(ClientState::Joining);
+}
+
+/// system responsible for leaving a server that was built with a different protocol
+///
+/// no further messages from the server can be trusted so the connection is closed
+fn receive_protocol_rejections(
+    mut commands: Commands,
+    mut messages: MessageReceiver<ProtocolRejected>,
+    mut disconnect_w: EventWriter<DisconnectFromServer>,
+    fingerprint: Res<ProtocolFingerprint>,
+) {
+    for ProtocolRejected { server_fingerprint } in messages.drain() {
+        error!(
+            "Server has protocol {} but the client has {}, the client and server are different versions",
+            server_fingerprint, *fingerprint
+        );
+
+        disconnect_w.send(DisconnectFromServer);
+        commands.insert_resource(DisconnectReason::Rejected(
+            JoinRejectReason::VersionMismatch,
+        ));
+    }
+}
+
+/// system responsible for entering [ClientState::Ingame] when the server accepts the join request
+///
+/// stores the [ResumeToken] issued by the server and the server's movement mode
+fn receive_join_responses(
+    mut commands: Commands,
+    client_state: Res<State<ClientState>>,
+    mut next_client_state: ResMut<NextState<ClientState>>,
+    mut accepted_messages: MessageReceiver<JoinAccepted>,
+    join_request: Option<Res<CurrentJoinRequest>>,
+    mut resume_tokens: ResMut<ResumeTokens>,
+) {
+    for JoinAccepted {
+        resume_token,
+        movement_mode,
+        tick_interval,
+    } in accepted_messages.drain()
+    {
+        let ClientState::Joining = client_state.get() else {
+            warn!("Received a join accepted message whilst not joining");
+            continue;
+        };
+
+        let Some(join_request) = join_request.as_ref() else {
+            error!("Received a join accepted message without a current join request");
+            continue;
+        };
+
+        info!("Joined server {}", join_request.server_addr);
+
+        resume_tokens
+            .tokens
+            .insert(join_request.server_addr, resume_token);
+
+        // the local player is predicted at the server's tick rate
+        commands.insert_resource(movement_mode);
+        commands.insert_resource(Time::<Fixed>::from_duration(tick_interval));
+
+        next_client_state.set(ClientState::Ingame);
+    }
+}
+
+/// system responsible for leaving the server when it rejects the join request
+fn receive_join_rejections(
+    mut commands: Commands,
+    mut messages: MessageReceiver<JoinRejected>,
+    mut disconnect_w: EventWriter<DisconnectFromServer>,
+) {
+    for JoinRejected { reason } in messages.drain() {
+        error!("Server rejected join request: {}", reason);
+
+        disconnect_w.send(DisconnectFromServer);
+        commands.insert_resource(DisconnectReason::Rejected(reason));
+    }
+}
+
+/// system responsible for returning to [ClientState::Disconnected] when the connection closes
+///
+/// will start reconnecting if the server issued a [ResumeToken]
+fn on_connection_lost(
+    mut commands: Commands,
+    client_state: Res<State<ClientState>>,
+    mut next_client_state: ResMut<NextState<ClientState>>,
+    join_request: Option<Res<CurrentJoinRequest>>,
+    resume_tokens: Res<ResumeTokens>,
+) {
+    if let ClientState::Disconnected = client_state.get() {
+        return;
+    }
+
+    next_client_state.set(ClientState::Disconnected);
+    commands.insert_resource(DisconnectReason::ConnectionLost);
+
+    let Some(join_request) = join_request else {
+        return;
+    };
+
+    if resume_tokens.get(join_request.server_addr).is_some() {
+        info!(
+            "Lost connection to {}, reconnecting in {:?}",
+            join_request.server_addr, RECONNECT_DELAY
+        );
+
+        commands.insert_resource(Reconnect {
+            timer: Timer::new(RECONNECT_DELAY, TimerMode::Once),
+        });
+    }
+}
+
+/// system responsible for reconnecting to the last server after a delay
+fn reconnect(
+    mut commands: Commands,
+    mut reconnect: ResMut<Reconnect>,
+    join_request: Res<CurrentJoinRequest>,
+    mut connect_w: EventWriter<ConnectToServer>,
+    time: Res<Time>,
+) {
+    if !reconnect.timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    commands.remove_resource::<Reconnect>();
+
+    connect_w.send(ConnectToServer {
+        server_addr: join_request.server_addr,
+        join_request: join_request.join_request.clone(),
+    });
+}
+fn update_connection_ui(
+    client_state: Res<State<ClientState>>,
+    disconnect_reason: Option<Res<DisconnectReason>>,
+    reconnect: Option<Res<Reconnect>>,
+    ui_elements: Res<UiElements>,
+    mut text_q: Query<&mut Text>,
+) {
+    let Ok(mut text) = text_q.get_mut(ui_elements.connection.status_text_entity) else {
+        error!("Couldn't query connection status text");
+        return;
+    };
 
-    info!("joining server: {}", join_request.join_request.username);
+    let status = match client_state.get() {
+        ClientState::Connecting => "Connecting...".to_string(),
+        ClientState::Joining => "Joining...".to_string(),
+        ClientState::Ingame => String::new(),
+        ClientState::Disconnected => match (disconnect_reason, reconnect) {
+            (Some(reason), Some(_)) => format!("{}\nReconnecting...", *reason),
+            (Some(reason), None) => reason.to_string(),
+            (None, _) => String::new(),
+        },
+    };
 
-    messages.send(*message_id, join_request.join_request.clone());
+    if text.0 != status {
+        text.0 = status;
+    }
 }
 
 /// highest level state for the client
@@ -91,7 +361,7 @@ pub enum ClientState {
     Disconnected,
     /// client is connecting to a server
     Connecting,
-    /// client has connected to the server and is waiting to be initialized
+    /// client has connected to the server and is waiting for a response to it's join request
     Joining,
     /// client has joined the game
     Ingame,
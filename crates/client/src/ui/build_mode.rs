@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+pub struct BuildModeUi {
+    pub status_text_entity: Entity,
+}
+
+impl BuildModeUi {
+    pub fn new(commands: &mut Commands, parent: Entity) -> Self {
+        // text showing the selected module and why the last placement was rejected
+        let status_text_entity = commands.spawn(Text::new("")).set_parent(parent).id();
+
+        Self { status_text_entity }
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+pub struct ConnectionUi {
+    pub status_text_entity: Entity,
+}
+
+impl ConnectionUi {
+    pub fn new(commands: &mut Commands, parent: Entity) -> Self {
+        // text in the center of the screen showing connection progress and errors
+        let status_text_entity = commands
+            .spawn((
+                Text::new(""),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ))
+            .set_parent(parent)
+            .id();
+
+        Self { status_text_entity }
+    }
+}
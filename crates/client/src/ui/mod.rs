@@ -1,6 +1,10 @@
 use bevy::{color::palettes::css::WHITE, prelude::*};
+use build_mode::BuildModeUi;
+use connection::ConnectionUi;
 use vitality::VitalityUi;
 
+pub mod build_mode;
+pub mod connection;
 pub mod vitality;
 
 pub fn build(app: &mut App) {
@@ -11,6 +15,8 @@ pub fn build(app: &mut App) {
 #[derive(Resource)]
 pub struct UiElements {
     pub vitality: VitalityUi,
+    pub connection: ConnectionUi,
+    pub build_mode: BuildModeUi,
 }
 
 fn create_ui(mut commands: Commands) {
@@ -39,5 +45,37 @@ fn create_ui(mut commands: Commands) {
 
     let vitality = VitalityUi::new(&mut commands, lower_left_quad_entity);
 
-    commands.insert_resource(UiElements { vitality });
+    // ui node that is centered on the screen
+    let center_entity = commands
+        .spawn(Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            position_type: PositionType::Absolute,
+            ..default()
+        })
+        .set_parent(root_node_entity)
+        .id();
+
+    let connection = ConnectionUi::new(&mut commands, center_entity);
+
+    // ui node that is aligned to the top left corner
+    let upper_left_quad_entity = commands
+        .spawn(Node {
+            left: Val::Px(20.),
+            top: Val::Px(20.),
+            position_type: PositionType::Absolute,
+            ..default()
+        })
+        .set_parent(root_node_entity)
+        .id();
+
+    let build_mode = BuildModeUi::new(&mut commands, upper_left_quad_entity);
+
+    commands.insert_resource(UiElements {
+        vitality,
+        connection,
+        build_mode,
+    });
 }
@@ -5,6 +5,7 @@ pub struct VitalityUi {
     pub pressure_level_entity: Entity,
     pub oxygen_level_entity: Entity,
     pub health_level_entity: Entity,
+    pub death_text_entity: Entity,
 }
 
 impl VitalityUi {
@@ -33,11 +34,17 @@ impl VitalityUi {
             .set_parent(vitality_node_entity)
             .id();
 
+        let death_text_entity = commands
+            .spawn(Text::new(""))
+            .set_parent(vitality_node_entity)
+            .id();
+
         Self {
             vitality_node_entity,
             pressure_level_entity,
             oxygen_level_entity,
             health_level_entity,
+            death_text_entity,
         }
     }
 }
@@ -0,0 +1,43 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{modules::SHIP_GRID_SCALE, physics::SimulationTick, GameLayer, ServerEntity};
+
+/// Size of a door, the door faces along the z axis.
+pub const DOOR_SIZE: Vec3 = Vec3::new(SHIP_GRID_SCALE, 2.2, 0.1);
+
+#[derive(Serialize, Deserialize)]
+pub struct NewDoor {
+    pub entity: ServerEntity,
+    pub open: bool,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateDoor {
+    pub entity: ServerEntity,
+    pub open: bool,
+    pub tick: SimulationTick,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestToggleDoor {
+    pub entity: ServerEntity,
+}
+
+pub fn door_collider() -> Collider {
+    Collider::cuboid(DOOR_SIZE.x, DOOR_SIZE.y, DOOR_SIZE.z)
+}
+
+/// Collision layers of a door's collider on both the client and server.
+///
+/// Closed doors are part of the world so they block the player controller.
+pub fn door_collision_layers(open: bool) -> CollisionLayers {
+    if open {
+        CollisionLayers::NONE
+    } else {
+        CollisionLayers::new([GameLayer::World], 0)
+    }
+}
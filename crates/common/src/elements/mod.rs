@@ -1,3 +1,4 @@
+pub mod door;
 pub mod room_vent;
 pub mod ship_map;
 pub mod tank;
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::ServerEntity;
+use crate::{physics::SimulationTick, ServerEntity};
 
 #[derive(Serialize, Deserialize)]
 pub struct NewRoomVent {
@@ -15,6 +15,7 @@ pub struct NewRoomVent {
 pub struct UpdateRoomVent {
     pub entity: ServerEntity,
     pub enabled: bool,
+    pub tick: SimulationTick,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::ServerEntity;
+use crate::{physics::SimulationTick, ServerEntity};
 
 /// Message from server to client to initialize a new ship map element
 #[derive(Serialize, Deserialize)]
@@ -23,6 +23,7 @@ pub struct ShipMapPosition {
 pub struct ShipMapPositionUpdate {
     pub entity: ServerEntity,
     pub position: ShipMapPosition,
+    pub tick: SimulationTick,
 }
 
 /// Message from client to server to request to move a ship map
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::ServerEntity;
+use crate::{physics::SimulationTick, ServerEntity};
 
 /// Message from server to client to initialize a new tank element
 #[derive(Serialize, Deserialize)]
@@ -23,6 +23,7 @@ pub struct TankState {
 pub struct UpdateTankState {
     pub entity: ServerEntity,
     pub state: TankState,
+    pub tick: SimulationTick,
 }
 
 /// Message from client to server to request to toggle the enabled state of a tank
@@ -36,4 +37,5 @@ pub struct RequestToggleTankEnabled {
 pub struct UpdateTankPercentage {
     pub entity: ServerEntity,
     pub percentage: f32,
+    pub tick: SimulationTick,
 }
@@ -37,6 +37,12 @@ impl From<ServerEntity> for Entity {
     }
 }
 
+/// Message from server to client when a replicated server entity is despawned.
+#[derive(Serialize, Deserialize)]
+pub struct DespawnEntity {
+    pub entity: ServerEntity,
+}
+
 #[derive(Default, PhysicsLayer)]
 pub enum GameLayer {
     /// Mostly static elements that players collide with
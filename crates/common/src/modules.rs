@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::ServerEntity;
 
+/// how many world units per ship grid
+pub const SHIP_GRID_SCALE: f32 = 2.;
+
 /// Message from server -> client to tell them
 /// to load a module mesh and collider.
 #[derive(Serialize, Deserialize)]
@@ -14,3 +17,166 @@ pub struct LoadModule {
     pub map_offset: Vec2,
     pub map_size: Vec2,
 }
+
+/// Message from server -> client when they join
+/// describing every type of ship module.
+#[derive(Serialize, Deserialize)]
+pub struct ShipModuleCatalog {
+    pub module_types: Vec<ShipModuleCatalogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShipModuleCatalogEntry {
+    /// the server's id for the module type, in the order module types were added
+    pub id: u32,
+    pub module_name: String,
+    /// grid spaces of the module before it is rotated
+    pub grid_spaces: Vec<IVec2>,
+    /// name of the module's files in `assets/ship_modules`
+    pub asset_path: String,
+    pub map_size: Vec2,
+}
+
+/// Position and rotation of a ship module in the ship grid.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ShipModuleTransform {
+    pub translation: IVec2,
+    pub rotation: ModuleRotation,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum ModuleRotation {
+    /// No rotation.
+    #[default]
+    East,
+    /// 90 degrees ccw.
+    North,
+    /// 90 degrees cw.
+    West,
+    /// 180 degrees.
+    South,
+}
+
+impl ShipModuleTransform {
+    pub fn to_world_transform(&self) -> Transform {
+        use ModuleRotation::*;
+        Transform {
+            translation: Vec3::new(
+                self.translation.x as f32 * SHIP_GRID_SCALE,
+                0.,
+                self.translation.y as f32 * SHIP_GRID_SCALE,
+            ),
+            rotation: match self.rotation {
+                East => Quat::IDENTITY,
+                North => Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+                West => Quat::from_rotation_y(std::f32::consts::PI),
+                South => Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2),
+            },
+            ..default()
+        }
+    }
+}
+
+impl ModuleRotation {
+    pub fn next_cw(self) -> Self {
+        use ModuleRotation::*;
+        match self {
+            East => South,
+            North => East,
+            West => North,
+            South => West,
+        }
+    }
+
+    pub fn next_ccw(self) -> Self {
+        use ModuleRotation::*;
+        match self {
+            East => North,
+            North => West,
+            West => South,
+            South => East,
+        }
+    }
+
+    /// Rotates a grid index the same way [ShipModuleTransform::to_world_transform] rotates the module.
+    pub fn rotate_index(self, index: IVec2) -> IVec2 {
+        use ModuleRotation::*;
+        match self {
+            East => IVec2::new(index.x, index.y),
+            North => IVec2::new(index.y, -index.x),
+            West => IVec2::new(-index.x, -index.y),
+            South => IVec2::new(-index.y, index.x),
+        }
+    }
+}
+
+/// Message from client -> server to place a ship module.
+///
+/// The server replies with [PlaceModuleRejected] if the module can't be placed.
+#[derive(Serialize, Deserialize)]
+pub struct RequestPlaceModule {
+    /// name of the module type
+    pub module_type: String,
+    pub transform: ShipModuleTransform,
+}
+
+/// Message from server -> client when a [RequestPlaceModule] was rejected.
+#[derive(Serialize, Deserialize)]
+pub struct PlaceModuleRejected {
+    pub reason: PlaceModuleRejectReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum PlaceModuleRejectReason {
+    UnknownModuleType,
+    /// the module would overlap an existing module
+    Overlapping,
+    /// none of the module's docking ports would link to the ship
+    NotAttached,
+}
+
+impl std::fmt::Display for PlaceModuleRejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlaceModuleRejectReason::UnknownModuleType => write!(f, "Unknown module type"),
+            PlaceModuleRejectReason::Overlapping => write!(f, "Overlaps another module"),
+            PlaceModuleRejectReason::NotAttached => {
+                write!(f, "Must connect to the ship through a docking port")
+            }
+        }
+    }
+}
+
+/// Message from client -> server to remove the ship module in a grid space.
+///
+/// The server replies with [RemoveModuleRejected] if the module can't be removed.
+#[derive(Serialize, Deserialize)]
+pub struct RequestRemoveModule {
+    /// any grid space of the module
+    pub index: IVec2,
+}
+
+/// Message from server -> client when a [RequestRemoveModule] was rejected.
+#[derive(Serialize, Deserialize)]
+pub struct RemoveModuleRejected {
+    pub reason: RemoveModuleRejectReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum RemoveModuleRejectReason {
+    /// there isn't a module in the grid space
+    NoModule,
+    /// removing the module would split the ship in two
+    WouldSplit,
+}
+
+impl std::fmt::Display for RemoveModuleRejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoveModuleRejectReason::NoModule => write!(f, "There isn't a module there"),
+            RemoveModuleRejectReason::WouldSplit => {
+                write!(f, "Removing this module would split the ship")
+            }
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Computes a fingerprint of the networking protocol.
+//!
+//! Message ids are assigned by registration order, so a client and server
+//! built from different commits can disagree on what a message id means.
+//! The fingerprint hashes the ordered type names of every message along with
+//! the shape serde uses for them, so any change to the protocol changes the fingerprint.
+//!
+//! Shapes are found by deserializing each message type from a [Tracer]
+//! that records every request the type makes of it.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+};
+
+use bevy::prelude::*;
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor},
+    Deserialize, Serialize,
+};
+
+use super::ProtocolRegistry;
+
+/// Fingerprint of the networking protocol.
+///
+/// Available as a resource on both the client and server.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ProtocolFingerprint(pub u64);
+
+impl Display for ProtocolFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl ProtocolFingerprint {
+    /// Computes the fingerprint of both the server -> client and client -> server protocols.
+    pub fn compute() -> Self {
+        let mut hasher = FingerprintHasher::default();
+
+        super::server_client_messages(&mut hasher);
+        hasher.write_str("|");
+        super::client_server_messages(&mut hasher);
+
+        ProtocolFingerprint(hasher.hash)
+    }
+}
+
+/// FNV-1a hasher that traces message shapes as they are registered.
+///
+/// [std::hash::DefaultHasher] isn't guaranteed to be stable between builds so isn't used.
+struct FingerprintHasher {
+    hash: u64,
+}
+
+impl Default for FingerprintHasher {
+    fn default() -> Self {
+        FingerprintHasher {
+            hash: 0xcbf29ce484222325,
+        }
+    }
+}
+
+impl FingerprintHasher {
+    fn write_str(&mut self, value: &str) {
+        for byte in value.bytes() {
+            self.hash ^= byte as u64;
+            self.hash = self.hash.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl ProtocolRegistry for FingerprintHasher {
+    fn add_message<T: Serialize + DeserializeOwned + Send + Sync + 'static>(&mut self) {
+        self.write_str(std::any::type_name::<T>());
+        self.write_str(&trace_shape::<T>());
+    }
+}
+
+/// Returns a description of the serde shape of `T`.
+///
+/// The type is traced once per enum variant so that the contents of every variant are seen.
+/// Enums nested inside a variant are traced with the same variant index, clamped to their variant count.
+///
+/// Panics if the type can't be traced, which happens for self describing formats
+/// that need `deserialize_any`, such as untagged enums.
+fn trace_shape<T: DeserializeOwned>() -> String {
+    let mut shape = String::new();
+    let mut variant_counts = HashMap::new();
+    let mut pass = 0;
+
+    loop {
+        let mut tracer = Tracer {
+            shape: &mut shape,
+            variant_counts: &mut variant_counts,
+            pass,
+        };
+
+        if let Err(err) = T::deserialize(&mut tracer) {
+            panic!(
+                "Couldn't trace the shape of message \"{}\": {}",
+                std::any::type_name::<T>(),
+                err
+            );
+        }
+
+        pass += 1;
+
+        if variant_counts.values().all(|&count| pass >= count) {
+            break;
+        }
+
+        shape.push(';');
+    }
+
+    shape
+}
+
+#[derive(Debug)]
+struct TraceError(String);
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl de::Error for TraceError {
+    fn custom<T: Display>(msg: T) -> Self {
+        TraceError(msg.to_string())
+    }
+}
+
+/// Deserializer that records the shape requested by a type.
+///
+/// Provides non zero values so that types that validate
+/// themselves during deserialization, such as [Entity] and [Dir3], succeed.
+struct Tracer<'a> {
+    shape: &'a mut String,
+    variant_counts: &'a mut HashMap<&'static str, usize>,
+    pass: usize,
+}
+
+impl<'a> Tracer<'a> {
+    fn push(&mut self, value: impl Display) {
+        let _ = write!(self.shape, "{}", value);
+    }
+
+    fn seq(&mut self, len: usize) -> TraceSeq<'_, 'a> {
+        TraceSeq {
+            tracer: self,
+            fields: &[],
+            remaining: len,
+        }
+    }
+
+    fn fields(&mut self, fields: &'static [&'static str]) -> TraceSeq<'_, 'a> {
+        TraceSeq {
+            tracer: self,
+            fields,
+            remaining: fields.len(),
+        }
+    }
+}
+
+macro_rules! trace_primitive {
+    ($($method:ident => $visit:ident($value:expr),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.push(stringify!($method).trim_start_matches("deserialize_"));
+                visitor.$visit($value)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut Tracer<'_> {
+    type Error = TraceError;
+
+    trace_primitive! {
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_i8(1),
+        deserialize_i16 => visit_i16(1),
+        deserialize_i32 => visit_i32(1),
+        deserialize_i64 => visit_i64(1),
+        deserialize_i128 => visit_i128(1),
+        deserialize_u8 => visit_u8(1),
+        deserialize_u16 => visit_u16(1),
+        deserialize_u32 => visit_u32(1),
+        // a valid entity with index 1 and generation 1
+        deserialize_u64 => visit_u64((1 << 32) | 1),
+        deserialize_u128 => visit_u128(1),
+        deserialize_f32 => visit_f32(1.),
+        deserialize_f64 => visit_f64(1.),
+        deserialize_char => visit_char('a'),
+        deserialize_str => visit_str(""),
+        deserialize_string => visit_string(String::new()),
+        deserialize_bytes => visit_bytes(&[]),
+        deserialize_byte_buf => visit_byte_buf(Vec::new()),
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.push("unit");
+        visitor.visit_unit()
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom(
+            "deserialize_any is not supported by the protocol",
+        ))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.push("option<");
+        let value = visitor.visit_some(&mut *self)?;
+        self.push(">");
+        Ok(value)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.push(name);
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.push(format_args!("{}(", name));
+        let value = visitor.visit_newtype_struct(&mut *self)?;
+        self.push(")");
+        Ok(value)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.push("seq<");
+        let value = visitor.visit_seq(self.seq(1))?;
+        self.push(">");
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.push("(");
+        let value = visitor.visit_seq(self.seq(len))?;
+        self.push(")");
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.push(name);
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.push("map<");
+        let value = visitor.visit_map(TraceMap {
+            tracer: self,
+            remaining: 1,
+        })?;
+        self.push(">");
+        Ok(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.push(format_args!("{}{{", name));
+        let value = visitor.visit_seq(self.fields(fields))?;
+        self.push("}");
+        Ok(value)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if variants.is_empty() {
+            return Err(de::Error::custom(format_args!(
+                "enum {} has no variants",
+                name
+            )));
+        }
+
+        self.variant_counts.insert(name, variants.len());
+        let variant = self.pass.min(variants.len() - 1);
+
+        self.push(format_args!("{}::{}", name, variants[variant]));
+        visitor.visit_enum(TraceEnum {
+            tracer: self,
+            variant: variant as u32,
+        })
+    }
+}
+
+/// Sequence of values for tuples, sequences and struct fields.
+struct TraceSeq<'a, 'b> {
+    tracer: &'a mut Tracer<'b>,
+    fields: &'static [&'static str],
+    remaining: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for TraceSeq<'_, '_> {
+    type Error = TraceError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        if let Some(field) = self
+            .fields
+            .get(self.fields.len().saturating_sub(self.remaining))
+        {
+            self.tracer.push(format_args!("{}:", field));
+        }
+
+        self.remaining -= 1;
+
+        let value = seed.deserialize(&mut *self.tracer)?;
+        self.tracer.push(",");
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Map with a single entry.
+struct TraceMap<'a, 'b> {
+    tracer: &'a mut Tracer<'b>,
+    remaining: usize,
+}
+
+impl<'de> de::MapAccess<'de> for TraceMap<'_, '_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        let key = seed.deserialize(&mut *self.tracer)?;
+        self.tracer.push(":");
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        seed.deserialize(&mut *self.tracer)
+    }
+}
+
+/// Enum access that selects the variant for the current pass.
+struct TraceEnum<'a, 'b> {
+    tracer: &'a mut Tracer<'b>,
+    variant: u32,
+}
+
+impl<'de, 'a, 'b> de::EnumAccess<'de> for TraceEnum<'a, 'b> {
+    type Error = TraceError;
+    type Variant = &'a mut Tracer<'b>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant: de::value::U32Deserializer<TraceError> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, self.tracer))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Tracer<'_> {
+    type Error = TraceError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        self.push("(");
+        let value = seed.deserialize(&mut *self)?;
+        self.push(")");
+        Ok(value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.push("{");
+        let value = visitor.visit_seq(self.fields(fields))?;
+        self.push("}");
+        Ok(value)
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use nevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub mod fingerprint;
+
+pub mod prelude {}
+
+/// sha256 of a DER encoded certificate as hex
+///
+/// logged by the server and pinned by clients
+pub fn certificate_fingerprint(certificate: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, certificate)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// marker component for server -> client messages
+#[derive(Component)]
+pub struct ServerClientMessages;
+
+/// marker component for client -> server messages
+#[derive(Component)]
+pub struct ClientServerMessages;
+
+/// something that messages can be registered to in protocol order
+///
+/// implemented by [ProtocolBuilder] and used to compute the [ProtocolFingerprint](fingerprint::ProtocolFingerprint)
+pub trait ProtocolRegistry {
+    fn add_message<T: Serialize + DeserializeOwned + Send + Sync + 'static>(&mut self);
+}
+
+impl<C: Send + Sync + 'static> ProtocolRegistry for ProtocolBuilder<C> {
+    fn add_message<T: Serialize + DeserializeOwned + Send + Sync + 'static>(&mut self) {
+        ProtocolBuilder::add_message::<T>(self);
+    }
+}
+
+pub fn server_client_protocol() -> ProtocolBuilder<ServerClientMessages> {
+    let mut protocol = ProtocolBuilder::new();
+    server_client_messages(&mut protocol);
+    protocol
+}
+
+pub fn client_server_protocol() -> ProtocolBuilder<ClientServerMessages> {
+    let mut protocol = ProtocolBuilder::new();
+    client_server_messages(&mut protocol);
+    protocol
+}
+
+/// registers server -> client messages
+///
+/// [ProtocolRejected] must stay first so that it can be understood by any client
+fn server_client_messages(protocol: &mut impl ProtocolRegistry) {
+    protocol.add_message::<ProtocolRejected>();
+    protocol.add_message::<crate::state::JoinAccepted>();
+    protocol.add_message::<crate::state::JoinRejected>();
+    protocol.add_message::<crate::physics::PhysicsSnapshot>();
+    protocol.add_message::<crate::physics::TimeSample>();
+    protocol.add_message::<crate::player::NewPlayer>();
+    protocol.add_message::<crate::player::PlayerLeft>();
+    protocol.add_message::<crate::player::vitality::UpdatePlayerVitality>();
+    protocol.add_message::<crate::modules::LoadModule>();
+    protocol.add_message::<crate::elements::ship_map::NewShipMap>();
+    protocol.add_message::<crate::elements::ship_map::ShipMapPositionUpdate>();
+    protocol.add_message::<crate::elements::tank::NewTank>();
+    protocol.add_message::<crate::elements::tank::UpdateTankState>();
+    protocol.add_message::<crate::elements::tank::UpdateTankPercentage>();
+    protocol.add_message::<crate::elements::room_vent::NewRoomVent>();
+    protocol.add_message::<crate::elements::room_vent::UpdateRoomVent>();
+    protocol.add_message::<crate::DespawnEntity>();
+    protocol.add_message::<crate::modules::PlaceModuleRejected>();
+    protocol.add_message::<crate::modules::ShipModuleCatalog>();
+    protocol.add_message::<crate::elements::door::NewDoor>();
+    protocol.add_message::<crate::elements::door::UpdateDoor>();
+    protocol.add_message::<crate::player::vitality::PlayerDied>();
+    protocol.add_message::<crate::player::PlayerRespawned>();
+    protocol.add_message::<crate::player::PlayerCorrection>();
+    protocol.add_message::<crate::player::PlayerInputAck>();
+    protocol.add_message::<crate::modules::RemoveModuleRejected>();
+}
+
+/// registers client -> server messages
+///
+/// [ProtocolHandshake] must stay first so that it can be understood by any server
+fn client_server_messages(protocol: &mut impl ProtocolRegistry) {
+    protocol.add_message::<ProtocolHandshake>();
+    protocol.add_message::<crate::state::JoinRequest>();
+    protocol.add_message::<crate::player::ClientPlayerUpdate>();
+    protocol.add_message::<crate::elements::ship_map::ShipMapMoveRequest>();
+    protocol.add_message::<crate::elements::tank::RequestToggleTankEnabled>();
+    protocol.add_message::<crate::elements::room_vent::RequestToggleRoomVentEnabled>();
+    protocol.add_message::<crate::modules::RequestPlaceModule>();
+    protocol.add_message::<crate::elements::door::RequestToggleDoor>();
+    protocol.add_message::<crate::player::ClientPlayerInput>();
+    protocol.add_message::<crate::modules::RequestRemoveModule>();
+}
+
+/// first message sent from client to server after connecting
+///
+/// the server won't accept a [JoinRequest](crate::state::JoinRequest)
+/// until it has received a matching fingerprint
+#[derive(Serialize, Deserialize)]
+pub struct ProtocolHandshake {
+    pub fingerprint: fingerprint::ProtocolFingerprint,
+}
+
+/// message from server to client when the [ProtocolHandshake] has a different fingerprint
+#[derive(Serialize, Deserialize)]
+pub struct ProtocolRejected {
+    pub server_fingerprint: fingerprint::ProtocolFingerprint,
+}
+
+/// enumerated stream headers
+pub enum StreamHeader {
+    Messages,
+}
+
+impl From<StreamHeader> for u16 {
+    fn from(value: StreamHeader) -> Self {
+        value as u16
+    }
+}
@@ -5,11 +5,20 @@ use serde::{Deserialize, Serialize};
 
 use crate::ServerEntity;
 
+/// Number of fixed timesteps the server has simulated.
+///
+/// Increases by one every tick and never goes backwards.
+#[derive(
+    Resource, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+pub struct SimulationTick(pub u64);
+
 /// Physics snapshot sent from server to client.
 #[derive(Serialize, Deserialize)]
 pub struct PhysicsSnapshot {
     pub bodies: Vec<(ServerEntity, PhysicsBodySnapshot)>,
     pub time: Duration,
+    pub tick: SimulationTick,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
@@ -27,4 +36,5 @@ pub struct PhysicsBodySnapshot {
 #[derive(Serialize, Deserialize)]
 pub struct TimeSample {
     pub time: Duration,
+    pub tick: SimulationTick,
 }
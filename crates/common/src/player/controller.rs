@@ -1,35 +1,62 @@
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 use serde::{Deserialize, Serialize};
 
+use super::player_collider;
 use crate::GameLayer;
 
-const PLAYER_ACCELERATION: f32 = 75.;
+pub const PLAYER_MOVE_SPEED: f32 = 5.;
+pub const PLAYER_JUMP_SPEED: f32 = 3.;
+pub const PLAYER_ACCELERATION: f32 = 75.;
+/// How far below a player the ground can be for them to jump.
+pub const ON_GROUND_TOLERANCE: f32 = 0.02;
 const MAX_INTEGRATE_ITERATIONS: usize = 20;
 const PLAYER_COLLISION_MARGIN: f32 = 0.0005;
 
-pub fn build_player_controller(app: &mut App) {
+/// Adds the player controller to the schedule that physics runs in.
+pub fn build_player_controller(app: &mut App, schedule: impl ScheduleLabel + Clone) {
     app.add_systems(
-        PostUpdate,
-        insert_missing_position_updates.in_set(PhysicsSet::Prepare),
+        schedule.clone(),
+        insert_missing_position_updates
+            .in_set(PhysicsSet::Prepare)
+            .in_set(PlayerControllerSet),
     );
 
     app.add_systems(
-        PostUpdate,
+        schedule,
         (
             (rotate_players, accelerate_players),
+            jump_players,
             integrate_players,
             update_player_positions,
         )
             .chain()
-            .in_set(PhysicsSet::StepSimulation),
+            .in_set(PhysicsSet::StepSimulation)
+            .in_set(PlayerControllerSet),
     );
 }
 
+/// System set containing the player controller systems added by [build_player_controller].
+#[derive(SystemSet, Hash, PartialEq, Eq, Debug, Clone)]
+pub struct PlayerControllerSet;
+
+/// Who decides where a player is, chosen by the server and sent to clients when they join.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MovementMode {
+    /// clients move their own player and send the result to the server, which validates it
+    #[default]
+    ClientAuthoritative,
+    /// clients only send inputs which the server simulates,
+    /// clients predict their own player and reconcile with the server's results
+    ServerAuthoritative,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone, Copy)]
 pub struct PlayerInput {
     pub target_velocity: Vec2,
     pub look_direction: Dir3,
+    /// jump if on the ground, cleared by the player controller once it has tried to jump
+    pub jump: bool,
 }
 
 impl Default for PlayerInput {
@@ -37,6 +64,7 @@ impl Default for PlayerInput {
         PlayerInput {
             target_velocity: Vec2::ZERO,
             look_direction: Dir3::NEG_Z,
+            jump: false,
         }
     }
 }
@@ -70,6 +98,46 @@ fn accelerate_players(
     }
 }
 
+/// Returns `true` if a player at `position` is within [ON_GROUND_TOLERANCE] of the ground.
+pub fn player_on_ground(spatial_query: &SpatialQuery, position: Vec3) -> bool {
+    let mut shape = player_collider();
+    shape.set_scale(Vec3::splat(0.99), 10);
+
+    spatial_query
+        .cast_shape(
+            &shape,
+            position,
+            Quat::IDENTITY,
+            Dir3::NEG_Y,
+            &ShapeCastConfig {
+                max_distance: ON_GROUND_TOLERANCE,
+                ignore_origin_penetration: true,
+                ..default()
+            },
+            &SpatialQueryFilter::from_mask([GameLayer::World]),
+        )
+        .is_some()
+}
+
+fn jump_players(
+    mut player_q: Query<(&mut PlayerInput, &Position, &mut LinearVelocity)>,
+    spatial_query: SpatialQuery,
+) {
+    for (mut input, &Position(position), mut velocity) in player_q.iter_mut() {
+        if !input.jump {
+            continue;
+        }
+
+        // jumps are edge triggered, a jump pressed in the air is dropped
+        input.jump = false;
+
+        // only jump when not already moving up so jumps don't stack just after leaving the ground
+        if player_on_ground(&spatial_query, position) && velocity.y <= 0. {
+            velocity.0 += Vec3::Y * PLAYER_JUMP_SPEED;
+        }
+    }
+}
+
 #[derive(Component, Default)]
 struct CharacterPositionUpdate(Vec3);
 
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use controller::PlayerInput;
@@ -8,6 +10,15 @@ use crate::ServerEntity;
 pub mod controller;
 pub mod vitality;
 
+/// Players below this height are respawned by the server.
+pub const RESET_FLOOR: f32 = -50.0;
+
+/// Height of a player's eyes above their position, where the camera is and interactions are measured from.
+pub const PLAYER_EYE_HEIGHT: f32 = 0.8;
+
+/// How far from their eyes a player can interact with elements.
+pub const INTERACTION_DISTANCE: f32 = 2.;
+
 /// Message from server to client to initialize a new player.
 #[derive(Serialize, Deserialize)]
 pub struct NewPlayer {
@@ -24,6 +35,50 @@ pub struct NewLocalPlayer {
     pub position: Vec3,
 }
 
+/// Message from server to client when a player is removed from the game.
+#[derive(Serialize, Deserialize)]
+pub struct PlayerLeft {
+    pub server_entity: ServerEntity,
+}
+
+/// Message from server to client when a player is respawned,
+/// sent to every client.
+///
+/// The player's client should move it's local player to the position.
+#[derive(Serialize, Deserialize)]
+pub struct PlayerRespawned {
+    pub server_entity: ServerEntity,
+    pub position: Vec3,
+}
+
+/// Message from server to client when the server rejected a [ClientPlayerUpdate].
+///
+/// The client should move it's local player back to this state.
+#[derive(Serialize, Deserialize)]
+pub struct PlayerCorrection {
+    pub position: Vec3,
+    pub linear_velocity: Vec3,
+}
+
+/// Message from client to server with one tick of input when the [MovementMode](controller::MovementMode)
+/// is server authoritative.
+///
+/// Sequence numbers increase by one every tick so the server can acknowledge them with a [PlayerInputAck].
+#[derive(Serialize, Deserialize)]
+pub struct ClientPlayerInput {
+    pub sequence: u32,
+    pub input: PlayerInput,
+}
+
+/// Message from server to client with the state of the client's player
+/// after the server simulated the [ClientPlayerInput] with this sequence number.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct PlayerInputAck {
+    pub sequence: u32,
+    pub position: Vec3,
+    pub linear_velocity: Vec3,
+}
+
 pub fn player_collider() -> Collider {
     Collider::capsule(0.25, 1.5)
 }
@@ -31,6 +86,9 @@ pub fn player_collider() -> Collider {
 /// Message from client to server to update player state.
 #[derive(Serialize, Deserialize)]
 pub struct ClientPlayerUpdate {
+    /// the client's time when the update was sent,
+    /// used by the server to measure how far the player could have moved since the last update
+    pub time: Duration,
     pub position: Vec3,
     pub linear_velocity: Vec3,
     pub input: PlayerInput,
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::{physics::SimulationTick, ServerEntity};
+
 pub const MAX_HEALTH: f32 = 100.0;
 pub const MAX_OXYGEN: f32 = 40.0;
 
@@ -29,4 +31,38 @@ impl Default for PlayerVitality {
 #[derive(Serialize, Deserialize)]
 pub struct UpdatePlayerVitality {
     pub vitality: PlayerVitality,
+    pub tick: SimulationTick,
+}
+
+/// Inserted on players on the server and client when their health reaches zero.
+#[derive(Component, Clone, Copy)]
+pub struct PlayerDead {
+    pub cause: DeathCause,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum DeathCause {
+    /// ran out of oxygen
+    Suffocation,
+    /// the module's pressure was too low
+    Decompression,
+}
+
+impl std::fmt::Display for DeathCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeathCause::Suffocation => write!(f, "Suffocated"),
+            DeathCause::Decompression => write!(f, "Decompressed"),
+        }
+    }
+}
+
+/// Server -> Client message when a player dies,
+/// sent to every client including the player's.
+///
+/// Also sent for players that are already dead when a client joins.
+#[derive(Serialize, Deserialize)]
+pub struct PlayerDied {
+    pub server_entity: ServerEntity,
+    pub cause: DeathCause,
 }
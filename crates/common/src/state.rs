@@ -1,11 +1,95 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
-use crate::ServerEntity;
+use crate::{player::controller::MovementMode, ServerEntity};
 
 /// message sent from client to server to request to join the current game
 #[derive(Serialize, Deserialize, Clone)]
 pub struct JoinRequest {
     pub username: String,
+    /// required if the server has a password
+    pub password: Option<String>,
+    /// token from a previous session on this server,
+    /// used to reclaim the player from that session
+    pub resume_token: Option<ResumeToken>,
+}
+
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 24;
+
+/// checks that a username has a valid length and only contains
+/// letters, numbers, spaces, underscores and dashes
+///
+/// usernames can't start or end with a space
+pub fn valid_username(username: &str) -> bool {
+    let length = username.chars().count();
+
+    (MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&length)
+        && username.trim() == username
+        && username
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-'))
+}
+
+/// secret issued by the server after joining
+///
+/// a client can send this in a later [JoinRequest] to
+/// reclaim it's player after losing connection
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ResumeToken(pub u128);
+
+/// message sent from server to client when a [JoinRequest] is accepted
+#[derive(Serialize, Deserialize)]
+pub struct JoinAccepted {
+    /// token for the session, can be used to resume it after reconnecting
+    pub resume_token: ResumeToken,
+    /// how the client should move it's player
+    pub movement_mode: MovementMode,
+    /// length of a server simulation tick, the client predicts it's player at the same rate
+    pub tick_interval: Duration,
+}
+
+/// message sent from server to client when a [JoinRequest] is rejected
+#[derive(Serialize, Deserialize)]
+pub struct JoinRejected {
+    pub reason: JoinRejectReason,
+}
+
+/// why a [JoinRequest] was rejected
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JoinRejectReason {
+    /// another player already has the username
+    DuplicateUsername,
+    /// the username is too short, too long or has invalid characters
+    InvalidUsername,
+    /// the server has reached it's player limit
+    ServerFull,
+    /// the password was missing or incorrect
+    BadPassword,
+    /// the client and server have different protocols
+    ///
+    /// sent as a [ProtocolRejected](crate::networking::ProtocolRejected)
+    /// so that it can be understood by any client
+    VersionMismatch,
+}
+
+impl std::fmt::Display for JoinRejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JoinRejectReason::DuplicateUsername => write!(f, "That username is already taken"),
+            JoinRejectReason::InvalidUsername => write!(
+                f,
+                "Usernames must be {} to {} letters, numbers, spaces, underscores or dashes",
+                MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH
+            ),
+            JoinRejectReason::ServerFull => write!(f, "The server is full"),
+            JoinRejectReason::BadPassword => write!(f, "Incorrect password"),
+            JoinRejectReason::VersionMismatch => {
+                write!(f, "The server is running a different version")
+            }
+        }
+    }
 }
 
 /// unique id for a player
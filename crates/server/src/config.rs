@@ -0,0 +1,327 @@
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use bevy::prelude::*;
+use common::player::controller::MovementMode;
+use serde::Deserialize;
+
+use crate::player::networking::OrphanedPlayerPolicy;
+
+/// Path of the config file if `--config` isn't given.
+const DEFAULT_CONFIG_PATH: &str = "server_config.ron";
+
+/// Settings for a server session.
+///
+/// Loaded from a ron file at startup, then any `--<key> <value>` arguments override the file.
+/// Keys are the field names with `_` replaced by `-`, e.g. `--breach-rate 0.2`.
+#[derive(Resource, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    /// if set clients must send this password to join
+    pub password: Option<String>,
+    /// maximum number of players, including players without a client
+    pub max_players: usize,
+    /// seconds a player stays after it's client disconnects so it can be resumed,
+    /// 0 despawns players as soon as their client disconnects
+    pub orphaned_player_grace_period_secs: u64,
+    /// length of a simulation tick in milliseconds, the server loop is paced to match
+    pub tick_interval_ms: u64,
+    /// time between physics snapshots in milliseconds
+    pub snapshot_interval_ms: u64,
+    /// time between time samples in milliseconds
+    pub time_sample_interval_ms: u64,
+    /// whether clients or the server decide where players are
+    pub movement_mode: MovementMode,
+    /// reject element interactions when world geometry is between the player and the element
+    pub interaction_line_of_sight: bool,
+    /// grid spaces of atmosphere lost per second from a breached module
+    pub breach_rate: f32,
+    /// portion of a module's volume that a vent can fill per second
+    pub vent_fill_rate: f32,
+    /// portion of the pressure difference between linked modules that is equalized per second,
+    /// at most the number of ticks per second
+    pub module_flow_rate: f32,
+    /// how much of a player's oxygen is refilled per unit of module atmosphere
+    pub player_oxygen_refill_ratio: f32,
+    /// how much of a player's oxygen is refilled per second
+    pub player_oxygen_refill_rate: f32,
+    /// health lost per second once a player runs out of oxygen
+    pub suffocation_damage_rate: f32,
+    /// pressure below which players start taking damage
+    pub low_pressure_threshold: f32,
+    /// health lost per second at zero pressure, scales down to nothing at the threshold
+    pub low_pressure_damage_rate: f32,
+    /// seconds after dying before a player respawns
+    pub respawn_delay_secs: f32,
+    /// file the ship is loaded from at startup and saved to
+    pub save_path: PathBuf,
+    /// seconds between autosaves, 0 disables autosaving
+    pub autosave_interval_secs: u64,
+    /// how many autosaves to keep, the oldest are removed first
+    pub autosave_count: usize,
+    /// directory autosaves are written to
+    pub autosave_dir: PathBuf,
+    /// resume from the newest autosave after an unclean shutdown without asking
+    pub resume_autosave: bool,
+    /// name of the scenario in `assets/scenarios` to start a new ship from
+    pub scenario: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            port: 27510,
+            password: None,
+            max_players: 16,
+            orphaned_player_grace_period_secs: 60,
+            tick_interval_ms: 10,
+            snapshot_interval_ms: 150,
+            time_sample_interval_ms: 100,
+            movement_mode: MovementMode::ClientAuthoritative,
+            interaction_line_of_sight: false,
+            breach_rate: 0.1,
+            vent_fill_rate: 0.05,
+            module_flow_rate: 0.5,
+            player_oxygen_refill_ratio: 50.,
+            player_oxygen_refill_rate: 10.,
+            suffocation_damage_rate: 5.,
+            low_pressure_threshold: 0.3,
+            low_pressure_damage_rate: 10.,
+            respawn_delay_secs: 5.,
+            save_path: "ship_save.ron".into(),
+            autosave_interval_secs: 300,
+            autosave_count: 5,
+            autosave_dir: "autosaves".into(),
+            resume_autosave: false,
+            scenario: "default".into(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the config file and command line.
+    ///
+    /// `server [port] [--config <path>] [--<key> <value>]...`
+    ///
+    /// Logs an error and returns `None` if anything is invalid.
+    pub fn load() -> Option<Self> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+
+        let config_path = args
+            .iter()
+            .position(|arg| arg == "--config")
+            .map(|index| args.get(index + 1).map(PathBuf::from));
+
+        let mut config = match config_path {
+            Some(None) => {
+                error!("expected a path after --config");
+                return None;
+            }
+            Some(Some(path)) => Self::read_file(&path, true)?,
+            None => Self::read_file(&PathBuf::from(DEFAULT_CONFIG_PATH), false)?,
+        };
+
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let Some(key) = arg.strip_prefix("--") else {
+                // a lone argument is the port, for compatibility
+                if let Err(err) = config.set("port", &arg) {
+                    error!("{}", err);
+                    return None;
+                }
+
+                continue;
+            };
+
+            let Some(value) = args.next() else {
+                error!("expected a value after --{}", key);
+                return None;
+            };
+
+            if key == "config" {
+                continue;
+            }
+
+            if let Err(err) = config.set(&key.replace('-', "_"), &value) {
+                error!("{}", err);
+                return None;
+            }
+        }
+
+        if let Err(err) = config.validate() {
+            error!("invalid server config: {}", err);
+            return None;
+        }
+
+        Some(config)
+    }
+
+    /// Reads a config file, `required` controls whether a missing file is an error.
+    fn read_file(path: &PathBuf, required: bool) -> Option<Self> {
+        let file = match std::fs::read_to_string(path) {
+            Ok(file) => file,
+            Err(err) if !required && err.kind() == std::io::ErrorKind::NotFound => {
+                info!("no config file at {:?}, using defaults", path);
+                return Some(ServerConfig::default());
+            }
+            Err(err) => {
+                error!("couldn't read config file {:?}: {}", path, err);
+                return None;
+            }
+        };
+
+        match ron::from_str(&file) {
+            Ok(config) => {
+                info!("loaded config file {:?}", path);
+                Some(config)
+            }
+            Err(err) => {
+                error!("invalid config file {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    /// Sets a field from a command line override.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value \"{}\" for {}", value, key))
+        }
+
+        match key {
+            "port" => self.port = parse(key, value)?,
+            "password" => self.password = Some(value.to_string()),
+            "max_players" => self.max_players = parse(key, value)?,
+            "orphaned_player_grace_period_secs" => {
+                self.orphaned_player_grace_period_secs = parse(key, value)?
+            }
+            "tick_interval_ms" => self.tick_interval_ms = parse(key, value)?,
+            "snapshot_interval_ms" => self.snapshot_interval_ms = parse(key, value)?,
+            "time_sample_interval_ms" => self.time_sample_interval_ms = parse(key, value)?,
+            "movement_mode" => {
+                self.movement_mode = match value {
+                    "ClientAuthoritative" => MovementMode::ClientAuthoritative,
+                    "ServerAuthoritative" => MovementMode::ServerAuthoritative,
+                    _ => return Err(format!("invalid value \"{}\" for {}", value, key)),
+                }
+            }
+            "interaction_line_of_sight" => self.interaction_line_of_sight = parse(key, value)?,
+            "breach_rate" => self.breach_rate = parse(key, value)?,
+            "vent_fill_rate" => self.vent_fill_rate = parse(key, value)?,
+            "module_flow_rate" => self.module_flow_rate = parse(key, value)?,
+            "player_oxygen_refill_ratio" => self.player_oxygen_refill_ratio = parse(key, value)?,
+            "player_oxygen_refill_rate" => self.player_oxygen_refill_rate = parse(key, value)?,
+            "suffocation_damage_rate" => self.suffocation_damage_rate = parse(key, value)?,
+            "low_pressure_threshold" => self.low_pressure_threshold = parse(key, value)?,
+            "low_pressure_damage_rate" => self.low_pressure_damage_rate = parse(key, value)?,
+            "respawn_delay_secs" => self.respawn_delay_secs = parse(key, value)?,
+            "save_path" => self.save_path = value.into(),
+            "autosave_interval_secs" => self.autosave_interval_secs = parse(key, value)?,
+            "autosave_count" => self.autosave_count = parse(key, value)?,
+            "autosave_dir" => self.autosave_dir = value.into(),
+            "resume_autosave" => self.resume_autosave = parse(key, value)?,
+            "scenario" => self.scenario = value.to_string(),
+            _ => return Err(format!("unknown config option \"{}\"", key)),
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.max_players == 0 {
+            return Err("max_players must be at least 1".into());
+        }
+
+        if self.autosave_count == 0 {
+            return Err("autosave_count must be at least 1".into());
+        }
+
+        for (name, interval) in [
+            ("tick_interval_ms", self.tick_interval_ms),
+            ("snapshot_interval_ms", self.snapshot_interval_ms),
+            ("time_sample_interval_ms", self.time_sample_interval_ms),
+        ] {
+            if interval == 0 {
+                return Err(format!("{} must be greater than 0", name));
+            }
+        }
+
+        for (name, rate) in [
+            ("breach_rate", self.breach_rate),
+            ("vent_fill_rate", self.vent_fill_rate),
+            ("module_flow_rate", self.module_flow_rate),
+            ("player_oxygen_refill_rate", self.player_oxygen_refill_rate),
+            ("suffocation_damage_rate", self.suffocation_damage_rate),
+            ("low_pressure_damage_rate", self.low_pressure_damage_rate),
+            ("respawn_delay_secs", self.respawn_delay_secs),
+        ] {
+            if !rate.is_finite() || rate < 0. {
+                return Err(format!("{} must be a positive number, got {}", name, rate));
+            }
+        }
+
+        // more than the whole pressure difference would flow every tick
+        let max_module_flow_rate = 1. / self.tick_interval().as_secs_f32();
+        if self.module_flow_rate > max_module_flow_rate {
+            return Err(format!(
+                "module_flow_rate can be at most {} with a tick_interval_ms of {}, got {}",
+                max_module_flow_rate, self.tick_interval_ms, self.module_flow_rate
+            ));
+        }
+
+        if !self.player_oxygen_refill_ratio.is_finite() || self.player_oxygen_refill_ratio <= 0. {
+            return Err(format!(
+                "player_oxygen_refill_ratio must be greater than 0, got {}",
+                self.player_oxygen_refill_ratio
+            ));
+        }
+
+        if let Err(err) = Duration::try_from_secs_f32(self.respawn_delay_secs) {
+            return Err(format!(
+                "respawn_delay_secs is invalid, got {}: {}",
+                self.respawn_delay_secs, err
+            ));
+        }
+
+        if !(0. ..=1.).contains(&self.low_pressure_threshold) {
+            return Err(format!(
+                "low_pressure_threshold must be between 0 and 1, got {}",
+                self.low_pressure_threshold
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_millis(self.tick_interval_ms)
+    }
+
+    pub fn snapshot_interval(&self) -> Duration {
+        Duration::from_millis(self.snapshot_interval_ms)
+    }
+
+    pub fn time_sample_interval(&self) -> Duration {
+        Duration::from_millis(self.time_sample_interval_ms)
+    }
+
+    /// checked by [ServerConfig::validate] so it can't overflow
+    pub fn respawn_delay(&self) -> Duration {
+        Duration::from_secs_f32(self.respawn_delay_secs)
+    }
+
+    pub fn orphaned_player_policy(&self) -> OrphanedPlayerPolicy {
+        match self.orphaned_player_grace_period_secs {
+            0 => OrphanedPlayerPolicy::Despawn,
+            secs => OrphanedPlayerPolicy::GracePeriod(Duration::from_secs(secs)),
+        }
+    }
+
+    /// `None` if autosaving is disabled
+    pub fn autosave_interval(&self) -> Option<Duration> {
+        (self.autosave_interval_secs > 0).then(|| Duration::from_secs(self.autosave_interval_secs))
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use common::{elements::door::*, physics::SimulationTick};
+use nevy::prelude::ReceivedMessages;
+
+use crate::{
+    modules::{atmosphere::AtmosphereGate, module_types::InitShipModules},
+    networking::prelude::*,
+    player::{interaction::PlayerInteraction, networking::ConnectedPlayer},
+    state::ReceiveGameUpdates,
+};
+
+use super::ElementUpdateMessageQueue;
+
+pub fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            (replicate_new_doors, replicate_existing_doors)
+                .chain()
+                .before(InitShipModules),
+            receive_door_toggle_requests,
+            (update_door_colliders, send_door_updates),
+        )
+            .chain(),
+    );
+}
+
+/// A door at a docking port, blocks players and atmosphere when closed.
+///
+/// Insert with an [AtmosphereGate] on the port the door is at.
+#[derive(Component)]
+#[require(Transform, ReplicateDespawn<ElementUpdateMessageQueue>)]
+pub struct Door {
+    pub open: bool,
+}
+
+fn replicate_new_doors(
+    door_q: Query<(Entity, &Door, &GlobalTransform), Added<Door>>,
+    client_q: Query<Entity, With<ReceiveGameUpdates>>,
+    mut messages: QueuedMessageSender<ElementUpdateMessageQueue>,
+    message_id: Res<MessageId<NewDoor>>,
+) {
+    for (door_entity, door, transform) in door_q.iter() {
+        for client_entity in client_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                NewDoor {
+                    entity: door_entity.into(),
+                    open: door.open,
+                    translation: transform.translation(),
+                    rotation: transform.rotation(),
+                },
+            );
+        }
+    }
+}
+
+fn replicate_existing_doors(
+    door_q: Query<(Entity, &Door, &GlobalTransform)>,
+    client_q: Query<Entity, Added<ReceiveGameUpdates>>,
+    mut messages: QueuedMessageSender<ElementUpdateMessageQueue>,
+    message_id: Res<MessageId<NewDoor>>,
+) {
+    for client_entity in client_q.iter() {
+        for (door_entity, door, transform) in door_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                NewDoor {
+                    entity: door_entity.into(),
+                    open: door.open,
+                    translation: transform.translation(),
+                    rotation: transform.rotation(),
+                },
+            );
+        }
+    }
+}
+
+fn receive_door_toggle_requests(
+    mut client_q: Query<(
+        Entity,
+        &mut ReceivedMessages<RequestToggleDoor>,
+        Option<&ConnectedPlayer>,
+    )>,
+    interaction: PlayerInteraction,
+    mut door_q: Query<&mut Door>,
+) {
+    for (client_entity, mut messages, connected_player) in client_q.iter_mut() {
+        for RequestToggleDoor { entity } in messages.drain() {
+            let Some(connected_player) = connected_player else {
+                warn!(
+                    "client {} sent a toggle door request when they weren't a player",
+                    client_entity
+                );
+                continue;
+            };
+
+            let door_entity = entity.into();
+
+            let Ok(mut door) = door_q.get_mut(door_entity) else {
+                warn!(
+                    "client {} tried to toggle a door that doesn't exist {}",
+                    client_entity, door_entity
+                );
+                continue;
+            };
+
+            if let Err(violation) = interaction.check(connected_player.get(), door_entity) {
+                warn!(
+                    "rejected client {}'s request to toggle door {}: {:?}",
+                    client_entity, door_entity, violation
+                );
+                continue;
+            }
+
+            door.open = !door.open;
+        }
+    }
+}
+
+/// Keeps the collider and atmosphere gate of doors in sync with whether they are open.
+fn update_door_colliders(
+    mut commands: Commands,
+    mut door_q: Query<(Entity, &Door, Option<&mut AtmosphereGate>), Changed<Door>>,
+) {
+    for (door_entity, door, gate) in door_q.iter_mut() {
+        commands
+            .entity(door_entity)
+            .insert((door_collider(), door_collision_layers(door.open)));
+
+        if let Some(mut gate) = gate {
+            gate.open = door.open;
+        }
+    }
+}
+
+/// Sends updates for doors that changed, including doors restored from a save after being replicated.
+fn send_door_updates(
+    door_q: Query<(Entity, &Door), Changed<Door>>,
+    client_q: Query<Entity, With<ReceiveGameUpdates>>,
+    mut messages: QueuedMessageSender<ElementUpdateMessageQueue>,
+    message_id: Res<MessageId<UpdateDoor>>,
+    tick: Res<SimulationTick>,
+) {
+    for (door_entity, door) in door_q.iter() {
+        for client_entity in client_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                UpdateDoor {
+                    entity: door_entity.into(),
+                    open: door.open,
+                    tick: *tick,
+                },
+            );
+        }
+    }
+}
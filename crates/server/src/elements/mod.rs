@@ -2,16 +2,21 @@ use bevy::prelude::*;
 
 use crate::networking::prelude::*;
 
+pub mod door;
 pub mod room_vent;
 pub mod ship_map;
 pub mod tank;
 
 pub fn build(app: &mut App) {
-    app.add_plugins(MessageQueuePlugin::<ElementUpdateMessageQueue>::default());
+    app.add_plugins((
+        MessageQueuePlugin::<ElementUpdateMessageQueue>::default(),
+        DespawnReplicationPlugin::<ElementUpdateMessageQueue>::default(),
+    ));
 
     ship_map::build(app);
     tank::build(app);
     room_vent::build(app);
+    door::build(app);
 }
 
 /// Marker type for the message queue used for element updates.
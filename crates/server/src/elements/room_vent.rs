@@ -1,9 +1,11 @@
 use bevy::prelude::*;
-use common::elements::room_vent::*;
+use common::{elements::room_vent::*, physics::SimulationTick};
 use nevy::prelude::ReceivedMessages;
 
 use crate::{
-    modules::atmosphere::ModuleVent, networking::prelude::*, player::networking::ConnectedPlayer,
+    modules::atmosphere::ModuleVent,
+    networking::prelude::*,
+    player::{interaction::PlayerInteraction, networking::ConnectedPlayer},
     state::ReceiveGameUpdates,
 };
 
@@ -24,7 +26,7 @@ pub fn build(app: &mut App) {
 }
 
 #[derive(Component)]
-#[require(Transform)]
+#[require(Transform, ReplicateDespawn<ElementUpdateMessageQueue>)]
 pub struct RoomVent {
     pub module_entity: Entity,
 }
@@ -100,15 +102,16 @@ fn receive_room_vent_toggle_enabled_requests(
     mut client_q: Query<(
         Entity,
         &mut ReceivedMessages<RequestToggleRoomVentEnabled>,
-        Has<ConnectedPlayer>,
+        Option<&ConnectedPlayer>,
     )>,
+    interaction: PlayerInteraction,
     room_vent_q: Query<&RoomVent>,
     mut module_vent_q: Query<&mut ModuleVent>,
     mut send_update_w: EventWriter<SendRoomVentUpdate>,
 ) {
-    for (client_entity, mut messages, has_player) in client_q.iter_mut() {
+    for (client_entity, mut messages, connected_player) in client_q.iter_mut() {
         for RequestToggleRoomVentEnabled { entity } in messages.drain() {
-            let true = has_player else {
+            let Some(connected_player) = connected_player else {
                 warn!(
                     "client {} sent a toggle room vent enabled request when they weren't a player",
                     client_entity
@@ -126,6 +129,14 @@ fn receive_room_vent_toggle_enabled_requests(
                 continue;
             };
 
+            if let Err(violation) = interaction.check(connected_player.get(), room_vent_entity) {
+                warn!(
+                    "rejected client {}'s request to toggle room vent {}: {:?}",
+                    client_entity, room_vent_entity, violation
+                );
+                continue;
+            }
+
             let Ok(mut module_vent) = module_vent_q.get_mut(room_vent.module_entity) else {
                 error!(
                     "couldn't query module vent {} for room vent {}",
@@ -148,6 +159,7 @@ fn send_room_vent_updates(
     client_q: Query<Entity, With<ReceiveGameUpdates>>,
     mut messages: QueuedMessageSender<ElementUpdateMessageQueue>,
     message_id: Res<MessageId<UpdateRoomVent>>,
+    tick: Res<SimulationTick>,
 ) {
     for &SendRoomVentUpdate { room_vent_entity } in send_update_r.read() {
         let Ok(room_vent) = room_vent_q.get(room_vent_entity) else {
@@ -170,6 +182,7 @@ fn send_room_vent_updates(
                 UpdateRoomVent {
                     entity: room_vent_entity.into(),
                     enabled: module_vent.open,
+                    tick: *tick,
                 },
             );
         }
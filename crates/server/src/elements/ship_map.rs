@@ -1,12 +1,15 @@
 use bevy::prelude::*;
-use common::elements::ship_map::{
-    NewShipMap, ShipMapMoveRequest, ShipMapPosition, ShipMapPositionUpdate,
+use common::{
+    elements::ship_map::{NewShipMap, ShipMapMoveRequest, ShipMapPosition, ShipMapPositionUpdate},
+    physics::SimulationTick,
 };
 use nevy::prelude::ReceivedMessages;
 
 use crate::{
-    modules::module_types::InitShipModules, networking::prelude::*,
-    player::networking::ConnectedPlayer, state::ReceiveGameUpdates,
+    modules::module_types::InitShipModules,
+    networking::prelude::*,
+    player::{interaction::PlayerInteraction, networking::ConnectedPlayer},
+    state::ReceiveGameUpdates,
 };
 
 use super::ElementUpdateMessageQueue;
@@ -26,6 +29,7 @@ pub fn build(app: &mut App) {
 /// Must be spawned in or after [InitShipModules] so that
 /// the global transform has been computed for network replication the next frame
 #[derive(Component)]
+#[require(ReplicateDespawn<ElementUpdateMessageQueue>)]
 pub struct ShipMap {
     pub position: Vec2,
     pub zoom: f32,
@@ -101,17 +105,19 @@ fn move_ship_maps(
     mut client_q: Query<(
         Entity,
         &mut ReceivedMessages<ShipMapMoveRequest>,
-        Has<ConnectedPlayer>,
+        Option<&ConnectedPlayer>,
     )>,
+    interaction: PlayerInteraction,
     mut map_q: Query<&mut ShipMap>,
 
     update_client_q: Query<Entity, With<ReceiveGameUpdates>>,
     mut sender: QueuedMessageSender<ElementUpdateMessageQueue>,
     message_id: Res<MessageId<ShipMapPositionUpdate>>,
+    tick: Res<SimulationTick>,
 ) {
-    for (client_entity, mut messages, has_player) in client_q.iter_mut() {
+    for (client_entity, mut messages, connected_player) in client_q.iter_mut() {
         for ShipMapMoveRequest { entity, delta } in messages.drain() {
-            let true = has_player else {
+            let Some(connected_player) = connected_player else {
                 warn!(
                     "client {} sent a ship map move request when they weren't a player",
                     client_entity
@@ -129,6 +135,14 @@ fn move_ship_maps(
                 continue;
             };
 
+            if let Err(violation) = interaction.check(connected_player.get(), map_entity) {
+                warn!(
+                    "rejected client {}'s request to move ship map {}: {:?}",
+                    client_entity, map_entity, violation
+                );
+                continue;
+            }
+
             map.position += delta;
 
             for client_entity in update_client_q.iter() {
@@ -141,6 +155,7 @@ fn move_ship_maps(
                             position: map.position,
                             zoom: map.zoom,
                         },
+                        tick: *tick,
                     },
                 )
             }
@@ -1,14 +1,17 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use common::elements::tank::{
-    NewTank, RequestToggleTankEnabled, TankState, UpdateTankPercentage, UpdateTankState,
+use common::{
+    elements::tank::{
+        NewTank, RequestToggleTankEnabled, TankState, UpdateTankPercentage, UpdateTankState,
+    },
+    physics::SimulationTick,
 };
 use nevy::prelude::ReceivedMessages;
 
 use crate::modules::module_types::InitShipModules;
 use crate::networking::prelude::*;
-use crate::player::networking::ConnectedPlayer;
+use crate::player::{interaction::PlayerInteraction, networking::ConnectedPlayer};
 use crate::{modules::atmosphere::TankAtmosphere, state::ReceiveGameUpdates};
 
 use super::ElementUpdateMessageQueue;
@@ -36,7 +39,7 @@ struct SendTankStateUpdate {
 
 /// Marker component for tank elements.
 #[derive(Component, Default)]
-#[require(TankAtmosphere, Transform)]
+#[require(TankAtmosphere, Transform, ReplicateDespawn<ElementUpdateMessageQueue>)]
 pub struct Tank;
 
 // #[derive(Bundle, Default)]
@@ -101,6 +104,7 @@ fn updated_changed_tank_state(
     client_q: Query<Entity, With<ReceiveGameUpdates>>,
     mut messages: QueuedMessageSender<ElementUpdateMessageQueue>,
     message_id: Res<MessageId<UpdateTankState>>,
+    tick: Res<SimulationTick>,
 ) {
     for &SendTankStateUpdate { tank_entity } in send_update_r.read() {
         let Ok(tank) = tank_q.get(tank_entity) else {
@@ -117,6 +121,7 @@ fn updated_changed_tank_state(
                     state: TankState {
                         enabled: tank.enabled,
                     },
+                    tick: *tick,
                 },
             );
         }
@@ -127,14 +132,15 @@ fn receive_tank_toggle_enabled_requests(
     mut client_q: Query<(
         Entity,
         &mut ReceivedMessages<RequestToggleTankEnabled>,
-        Has<ConnectedPlayer>,
+        Option<&ConnectedPlayer>,
     )>,
+    interaction: PlayerInteraction,
     mut tank_q: Query<&mut TankAtmosphere>,
     mut send_update_w: EventWriter<SendTankStateUpdate>,
 ) {
-    for (client_entity, mut messages, has_player) in client_q.iter_mut() {
+    for (client_entity, mut messages, connected_player) in client_q.iter_mut() {
         for RequestToggleTankEnabled { entity } in messages.drain() {
-            let true = has_player else {
+            let Some(connected_player) = connected_player else {
                 warn!(
                     "client {} sent a toggle tank enabled request when they weren't a player",
                     client_entity
@@ -152,6 +158,14 @@ fn receive_tank_toggle_enabled_requests(
                 continue;
             };
 
+            if let Err(violation) = interaction.check(connected_player.get(), tank_entity) {
+                warn!(
+                    "rejected client {}'s request to toggle tank {}: {:?}",
+                    client_entity, tank_entity, violation
+                );
+                continue;
+            }
+
             tank.enabled = !tank.enabled;
             send_update_w.send(SendTankStateUpdate { tank_entity });
         }
@@ -164,6 +178,7 @@ fn send_tank_percentage_updates(
     mut message_sender: MessageSender,
     message_id: Res<MessageId<UpdateTankPercentage>>,
     time: Res<Time>,
+    tick: Res<SimulationTick>,
     mut last_update: Local<Duration>,
 ) {
     if time.elapsed() - *last_update > TANK_PERCENTAGE_UPDATE_INTERVAL {
@@ -173,6 +188,7 @@ fn send_tank_percentage_updates(
             let message = UpdateTankPercentage {
                 entity: tank_entity.into(),
                 percentage: tank.level / tank.volume,
+                tick: *tick,
             };
 
             for client_entity in client_q.iter() {
@@ -6,15 +6,20 @@ use bevy::{
     prelude::*,
 };
 use common::mesh_colliders::GltfColliderPlugin;
+use config::ServerConfig;
 
+pub mod config;
 pub mod elements;
 pub mod modules;
 pub mod networking;
 pub mod physics;
 pub mod player;
+pub mod save;
+pub mod scenario;
 pub mod state;
 
-const SERVER_TICK_INTERVAL: Duration = Duration::from_millis(10);
+/// Path of the assets folder relative to the server's working directory.
+const ASSET_PATH: &str = "../../assets";
 
 fn main() {
     let mut app = App::new();
@@ -27,15 +32,16 @@ fn main() {
     });
 
     // load server config
-    let Some(server_config) = ServerConfig::new() else {
+    let Some(server_config) = ServerConfig::load() else {
         return;
     };
     app.insert_resource(server_config);
 
     // build rest of app
     app.add_plugins(MinimalPlugins);
+    app.add_plugins(bevy::app::TerminalCtrlCHandlerPlugin);
     app.add_plugins(AssetPlugin {
-        file_path: "../../assets".into(),
+        file_path: ASSET_PATH.into(),
         ..default()
     });
     app.add_plugins(bevy::scene::ScenePlugin);
@@ -52,34 +58,14 @@ fn main() {
     player::build(&mut app);
     modules::build(&mut app);
     elements::build(&mut app);
+    save::build(&mut app);
+    scenario::build(&mut app);
 
     app.add_systems(Last, tick_delay);
 
     app.run();
 }
 
-#[derive(Resource)]
-struct ServerConfig {
-    pub port: u16,
-}
-
-impl ServerConfig {
-    fn new() -> Option<Self> {
-        let port = if let Some(port) = std::env::args().nth(1) {
-            let Ok(port) = port.parse() else {
-                error!("invalid port format \"{}\"", port);
-                return None;
-            };
-
-            port
-        } else {
-            27510
-        };
-
-        Some(ServerConfig { port })
-    }
-}
-
 #[derive(Resource)]
 struct ServerStart(Instant);
 
@@ -89,9 +75,13 @@ impl Default for ServerStart {
     }
 }
 
-fn tick_delay(server_start: Local<ServerStart>, mut last_tick: Local<Duration>) {
+fn tick_delay(
+    server_config: Res<ServerConfig>,
+    server_start: Local<ServerStart>,
+    mut last_tick: Local<Duration>,
+) {
     let elapsed = server_start.0.elapsed().saturating_sub(*last_tick);
-    let remaining = SERVER_TICK_INTERVAL.saturating_sub(elapsed);
+    let remaining = server_config.tick_interval().saturating_sub(elapsed);
 
     std::thread::sleep(remaining);
 
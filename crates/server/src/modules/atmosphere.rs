@@ -1,12 +1,34 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::connections::{DockingPort, ShipModuleConnections};
 
 pub fn build(app: &mut App) {
-    app.insert_resource(BreachRate { rate: 0.1 });
-    app.insert_resource(VentFillRate { rate: 0.05 });
+    let server_config = app.world().resource::<crate::ServerConfig>();
+    let breach_rate = BreachRate {
+        rate: server_config.breach_rate,
+    };
+    let vent_fill_rate = VentFillRate {
+        rate: server_config.vent_fill_rate,
+    };
+    let module_flow_rate = ModuleFlowRate {
+        rate: server_config.module_flow_rate,
+    };
+
+    app.insert_resource(breach_rate);
+    app.insert_resource(vent_fill_rate);
+    app.insert_resource(module_flow_rate);
 
     app.add_systems(
-        Update,
-        (fill_atmospheres, drain_breached_atmospheres).chain(),
+        FixedUpdate,
+        (
+            fill_atmospheres,
+            flow_between_modules,
+            drain_breached_atmospheres,
+        )
+            .chain(),
     );
 }
 
@@ -42,6 +64,22 @@ pub struct VentFillRate {
     pub rate: f32,
 }
 
+/// Closes the link between two modules at a docking port when not open,
+/// stopping atmosphere from flowing between them.
+///
+/// The port is in ship grid coordinates and can be the port on either side of the link.
+#[derive(Component)]
+pub struct AtmosphereGate {
+    pub port: DockingPort,
+    pub open: bool,
+}
+
+#[derive(Resource)]
+pub struct ModuleFlowRate {
+    /// Portion of the pressure difference between linked modules equalized per second
+    pub rate: f32,
+}
+
 #[derive(Resource)]
 pub struct BreachRate {
     /// Grid spaces of atmosphere lost per second
@@ -60,6 +98,74 @@ fn drain_breached_atmospheres(
     }
 }
 
+/// Moves atmosphere from high to low pressure between modules linked by docking ports.
+fn flow_between_modules(
+    mut module_q: Query<&mut ModuleAtmosphere>,
+    gate_q: Query<&AtmosphereGate>,
+    connections: Res<ShipModuleConnections>,
+    flow_rate: Res<ModuleFlowRate>,
+    time: Res<Time>,
+) {
+    let closed_ports: HashSet<DockingPort> = gate_q
+        .iter()
+        .filter(|gate| !gate.open)
+        .map(|gate| gate.port)
+        .collect();
+
+    let equalize_portion = (flow_rate.rate * time.delta_secs()).min(1.);
+
+    let open_links: Vec<_> = connections
+        .links()
+        .iter()
+        .filter(|link| {
+            !closed_ports.contains(&link.port) && !closed_ports.contains(&link.port.opposite())
+        })
+        .collect();
+
+    let mut open_link_counts: HashMap<Entity, u32> = HashMap::new();
+    for link in open_links.iter() {
+        *open_link_counts.entry(link.module_a).or_default() += 1;
+        *open_link_counts.entry(link.module_b).or_default() += 1;
+    }
+
+    // flows are summed before being applied so that the order of links doesn't matter
+    let mut changes: HashMap<Entity, f32> = HashMap::new();
+
+    for link in open_links {
+        let Ok([atmosphere_a, atmosphere_b]) = module_q.get_many([link.module_a, link.module_b])
+        else {
+            continue;
+        };
+
+        if atmosphere_a.volume <= 0. || atmosphere_b.volume <= 0. {
+            continue;
+        }
+
+        let pressure_difference =
+            atmosphere_a.level / atmosphere_a.volume - atmosphere_b.level / atmosphere_b.volume;
+
+        // amount that would make both pressures equal
+        let equalizing_flow = pressure_difference * atmosphere_a.volume * atmosphere_b.volume
+            / (atmosphere_a.volume + atmosphere_b.volume);
+
+        // a module's flow is shared between it's links,
+        // so that it can't lose more atmosphere than it has or gain more than it can hold
+        let link_count = open_link_counts[&link.module_a].max(open_link_counts[&link.module_b]);
+        let flow = equalizing_flow * equalize_portion / link_count as f32;
+
+        *changes.entry(link.module_a).or_default() -= flow;
+        *changes.entry(link.module_b).or_default() += flow;
+    }
+
+    for (module_entity, change) in changes {
+        let Ok(mut atmosphere) = module_q.get_mut(module_entity) else {
+            continue;
+        };
+
+        atmosphere.level = (atmosphere.level + change).clamp(0., atmosphere.volume);
+    }
+}
+
 fn fill_atmospheres(
     mut module_q: Query<(&ModuleVent, &mut ModuleAtmosphere)>,
     fill_rate: Res<VentFillRate>,
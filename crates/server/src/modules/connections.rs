@@ -0,0 +1,196 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::Deserialize;
+
+use super::{
+    grid::ShipModuleTransform,
+    module_types::{ShipModule, ShipModuleTypes, SpawnShipModules},
+};
+
+pub fn build(app: &mut App) {
+    app.init_resource::<ShipModuleConnections>();
+
+    app.add_systems(Update, update_module_connections.after(SpawnShipModules));
+}
+
+/// A port on the edge of one of a module's grid spaces that can link to another module.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct DockingPort {
+    /// the grid space of the module the port is on
+    pub space: IVec2,
+    /// the edge of the grid space the port is on, pointing towards the space it connects to
+    ///
+    /// must be one of `(1, 0)`, `(-1, 0)`, `(0, 1)` or `(0, -1)`
+    pub direction: IVec2,
+}
+
+impl DockingPort {
+    /// Returns `true` if the direction points to an adjacent grid space.
+    pub fn is_valid(&self) -> bool {
+        self.direction.abs().element_sum() == 1
+    }
+
+    /// Rotates and moves the port with a module's transform.
+    pub fn transformed(&self, transform: ShipModuleTransform) -> DockingPort {
+        DockingPort {
+            space: transform.rotation.rotate_index(self.space) + transform.translation,
+            direction: transform.rotation.rotate_index(self.direction),
+        }
+    }
+
+    /// The port that would link to this one.
+    pub fn opposite(&self) -> DockingPort {
+        DockingPort {
+            space: self.space + self.direction,
+            direction: -self.direction,
+        }
+    }
+}
+
+/// Graph of which ship modules are linked through matching docking ports.
+///
+/// Recomputed whenever modules are added or removed.
+#[derive(Resource, Default)]
+pub struct ShipModuleConnections {
+    connections: HashMap<Entity, Vec<Entity>>,
+    /// every port on the ship and the module it belongs to
+    ports: HashMap<DockingPort, Entity>,
+    links: Vec<ModuleLink>,
+}
+
+/// Two modules linked through a pair of matching docking ports.
+#[derive(Clone, Copy, Debug)]
+pub struct ModuleLink {
+    /// the port on `module_a`, the port on `module_b` is it's opposite
+    pub port: DockingPort,
+    pub module_a: Entity,
+    pub module_b: Entity,
+}
+
+impl ShipModuleConnections {
+    /// Gets the modules linked to a module.
+    pub fn connected(&self, module_entity: Entity) -> &[Entity] {
+        self.connections
+            .get(&module_entity)
+            .map(|connected| &connected[..])
+            .unwrap_or(&[])
+    }
+
+    /// Gets the module that a transformed port belongs to.
+    pub fn module_at_port(&self, port: DockingPort) -> Option<Entity> {
+        self.ports.get(&port).copied()
+    }
+
+    /// Returns `true` if removing a module would disconnect any of the modules linked to it from each other.
+    pub fn would_split(&self, module_entity: Entity) -> bool {
+        let neighbours = self.connected(module_entity);
+
+        let Some(&start) = neighbours.first() else {
+            return false;
+        };
+
+        let mut visited = HashSet::new();
+        visited.insert(module_entity);
+        visited.insert(start);
+
+        let mut stack = vec![start];
+
+        while let Some(current) = stack.pop() {
+            for &next in self.connected(current) {
+                if visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        !neighbours
+            .iter()
+            .all(|neighbour| visited.contains(neighbour))
+    }
+
+    /// Removes a module from the graph.
+    ///
+    /// Used to keep the graph correct when several modules are removed at once,
+    /// it is recomputed after modules are removed anyway.
+    pub fn remove_module(&mut self, module_entity: Entity) {
+        if let Some(connected) = self.connections.remove(&module_entity) {
+            for other_module_entity in connected {
+                if let Some(other_connected) = self.connections.get_mut(&other_module_entity) {
+                    other_connected.retain(|&entity| entity != module_entity);
+                }
+            }
+        }
+
+        self.ports.retain(|_, entity| *entity != module_entity);
+        self.links
+            .retain(|link| link.module_a != module_entity && link.module_b != module_entity);
+    }
+
+    /// Gets every pair of linked docking ports.
+    pub fn links(&self) -> &[ModuleLink] {
+        &self.links
+    }
+}
+
+fn update_module_connections(
+    module_q: Query<(Entity, &ShipModule, &ShipModuleTransform)>,
+    added_q: Query<(), Added<ShipModule>>,
+    mut removed_r: RemovedComponents<ShipModule>,
+    modules: Res<ShipModuleTypes>,
+    mut connections: ResMut<ShipModuleConnections>,
+) {
+    let removed = removed_r.read().count() > 0;
+
+    if added_q.is_empty() && !removed {
+        return;
+    }
+
+    // every port on the ship and the module it belongs to
+    let mut ports = HashMap::new();
+
+    for (module_entity, module, &transform) in module_q.iter() {
+        for port in modules.ports(module.module_type_id) {
+            ports.insert(port.transformed(transform), module_entity);
+        }
+    }
+
+    let mut new_connections: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut links = Vec::new();
+
+    for (port, &module_entity) in ports.iter() {
+        let Some(&other_module_entity) = ports.get(&port.opposite()) else {
+            continue;
+        };
+
+        if other_module_entity == module_entity {
+            continue;
+        }
+
+        // each pair of ports is visited from both sides, only keep one link
+        if module_entity < other_module_entity {
+            links.push(ModuleLink {
+                port: *port,
+                module_a: module_entity,
+                module_b: other_module_entity,
+            });
+        }
+
+        let connected = new_connections.entry(module_entity).or_default();
+
+        if !connected.contains(&other_module_entity) {
+            connected.push(other_module_entity);
+        }
+    }
+
+    debug!(
+        "Updated ship module connections, {} links",
+        new_connections.values().map(Vec::len).sum::<usize>() / 2
+    );
+
+    connections.connections = new_connections;
+    connections.ports = ports;
+    connections.links = links;
+}
@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use common::modules::{
+    PlaceModuleRejectReason, PlaceModuleRejected, RemoveModuleRejectReason, RemoveModuleRejected,
+    RequestPlaceModule, RequestRemoveModule,
+};
+use nevy::prelude::ReceivedMessages;
+
+use crate::{networking::prelude::*, player::networking::ConnectedPlayer};
+
+use super::{
+    connections::ShipModuleConnections,
+    grid::ShipModuleGrid,
+    module_types::{
+        DespawnShipModule, ShipModule, ShipModuleTypes, SpawnShipModule, SpawnShipModules,
+    },
+    networking::LoadModuleMessageQueue,
+};
+
+pub fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            receive_place_module_requests,
+            receive_remove_module_requests,
+        )
+            .before(SpawnShipModules),
+    );
+}
+
+/// Validates module placement requests from players and spawns the modules,
+/// replying with [PlaceModuleRejected] if a module can't be placed.
+///
+/// New modules must link to the ship through a docking port, unless the ship is empty.
+fn receive_place_module_requests(
+    mut client_q: Query<(
+        Entity,
+        &mut ReceivedMessages<RequestPlaceModule>,
+        Has<ConnectedPlayer>,
+    )>,
+    modules: Res<ShipModuleTypes>,
+    grid: Res<ShipModuleGrid>,
+    connections: Res<ShipModuleConnections>,
+    module_q: Query<(), With<ShipModule>>,
+    mut spawn_module_w: EventWriter<SpawnShipModule>,
+    mut messages: QueuedMessageSender<LoadModuleMessageQueue>,
+    rejected_message_id: Res<MessageId<PlaceModuleRejected>>,
+) {
+    // spaces taken by modules placed this tick, which aren't in the grid yet
+    let mut claimed_spaces = Vec::new();
+    let mut claimed_ports = Vec::new();
+
+    for (client_entity, mut requests, has_player) in client_q.iter_mut() {
+        for RequestPlaceModule {
+            module_type,
+            transform,
+        } in requests.drain()
+        {
+            let true = has_player else {
+                warn!(
+                    "client {} sent a place module request when they weren't a player",
+                    client_entity
+                );
+                continue;
+            };
+
+            let mut reject = |reason| {
+                debug!(
+                    "rejected client {}'s request to place \"{}\": {}",
+                    client_entity, module_type, reason
+                );
+
+                messages.send(
+                    *rejected_message_id,
+                    client_entity,
+                    PlaceModuleRejected { reason },
+                );
+            };
+
+            let Some((module_type_id, grid_spaces)) = modules
+                .find(&module_type)
+                .and_then(|id| Some((id, modules.grid_spaces(id)?)))
+            else {
+                reject(PlaceModuleRejectReason::UnknownModuleType);
+                continue;
+            };
+
+            let overlapping = !grid_spaces.fits_in_grid(transform, &grid)
+                || grid_spaces
+                    .spaces_transformed(transform)
+                    .any(|space| claimed_spaces.contains(&space));
+
+            if overlapping {
+                reject(PlaceModuleRejectReason::Overlapping);
+                continue;
+            }
+
+            let ports: Vec<_> = modules
+                .ports(module_type_id)
+                .iter()
+                .map(|port| port.transformed(transform))
+                .collect();
+
+            let ship_is_empty = module_q.is_empty() && claimed_ports.is_empty();
+
+            let attached = ports.iter().any(|port| {
+                let opposite = port.opposite();
+
+                connections.module_at_port(opposite).is_some() || claimed_ports.contains(&opposite)
+            });
+
+            if !ship_is_empty && !attached {
+                reject(PlaceModuleRejectReason::NotAttached);
+                continue;
+            }
+
+            claimed_spaces.extend(grid_spaces.spaces_transformed(transform));
+            claimed_ports.extend(ports);
+
+            info!(
+                "client {} placed \"{}\" at {} {:?}",
+                client_entity, module_type, transform.translation, transform.rotation
+            );
+
+            spawn_module_w.send(SpawnShipModule {
+                module_type_id,
+                transform,
+            });
+        }
+    }
+}
+
+/// Validates module removal requests from players and despawns the modules,
+/// replying with [RemoveModuleRejected] if a module can't be removed.
+///
+/// Modules can't be removed if it would split the ship.
+fn receive_remove_module_requests(
+    mut client_q: Query<(
+        Entity,
+        &mut ReceivedMessages<RequestRemoveModule>,
+        Has<ConnectedPlayer>,
+    )>,
+    grid: Res<ShipModuleGrid>,
+    connections: Res<ShipModuleConnections>,
+    mut despawn_module_w: EventWriter<DespawnShipModule>,
+    mut messages: QueuedMessageSender<LoadModuleMessageQueue>,
+    rejected_message_id: Res<MessageId<RemoveModuleRejected>>,
+) {
+    for (client_entity, mut requests, has_player) in client_q.iter_mut() {
+        for RequestRemoveModule { index } in requests.drain() {
+            let true = has_player else {
+                warn!(
+                    "client {} sent a remove module request when they weren't a player",
+                    client_entity
+                );
+                continue;
+            };
+
+            let mut reject = |reason| {
+                debug!(
+                    "rejected client {}'s request to remove the module at {}: {}",
+                    client_entity, index, reason
+                );
+
+                messages.send(
+                    *rejected_message_id,
+                    client_entity,
+                    RemoveModuleRejected { reason },
+                );
+            };
+
+            let Some(module_entity) = grid.get(index) else {
+                reject(RemoveModuleRejectReason::NoModule);
+                continue;
+            };
+
+            // despawning checks again in case several modules are removed this tick
+            if connections.would_split(module_entity) {
+                reject(RemoveModuleRejectReason::WouldSplit);
+                continue;
+            }
+
+            info!(
+                "client {} removed module {} at {}",
+                client_entity, module_entity, index
+            );
+
+            despawn_module_w.send(DespawnShipModule {
+                module_entity,
+                allow_split: false,
+            });
+        }
+    }
+}
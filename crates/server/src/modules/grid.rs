@@ -2,14 +2,15 @@ use std::collections::VecDeque;
 
 use avian3d::prelude::PhysicsSet;
 use bevy::prelude::*;
-
-/// how many world units per ship grid
-pub const SHIP_GRID_SCALE: f32 = 2.;
+pub use common::modules::{ModuleRotation, ShipModuleTransform, SHIP_GRID_SCALE};
 
 pub fn build(app: &mut App) {
     app.init_resource::<ShipModuleGrid>();
 
-    app.add_systems(PostUpdate, update_grid_presence.after(PhysicsSet::Sync));
+    app.add_systems(
+        FixedPostUpdate,
+        update_grid_presence.after(PhysicsSet::Sync),
+    );
 }
 
 /// Server side resource that contains the locations of modules in the grid
@@ -141,87 +142,16 @@ impl ShipModuleGrid {
         while self.grid.iter().all(|row| row.back().unwrap().is_none()) {
             for row in self.grid.iter_mut() {
                 row.pop_back();
-                self.bound.max.x -= 1;
             }
+            self.bound.max.x -= 1;
         }
 
         // remove empty collumns from the negative x edge
         while self.grid.iter().all(|row| row.front().unwrap().is_none()) {
             for row in self.grid.iter_mut() {
                 row.pop_front();
-                self.bound.min.x += 1;
             }
-        }
-    }
-}
-
-#[derive(Component, Clone, Copy)]
-pub struct ShipModuleTransform {
-    pub translation: IVec2,
-    pub rotation: ModuleRotation,
-}
-
-#[derive(Clone, Copy, Debug, Default)]
-pub enum ModuleRotation {
-    /// No rotation.
-    #[default]
-    East,
-    /// 90 degrees ccw.
-    North,
-    /// 90 degrees cw.
-    West,
-    /// 180 degrees.
-    South,
-}
-
-impl ShipModuleTransform {
-    pub fn to_world_transform(&self) -> Transform {
-        use ModuleRotation::*;
-        Transform {
-            translation: Vec3::new(
-                self.translation.x as f32 * SHIP_GRID_SCALE,
-                0.,
-                self.translation.y as f32 * SHIP_GRID_SCALE,
-            ),
-            rotation: match self.rotation {
-                East => Quat::IDENTITY,
-                North => Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
-                West => Quat::from_rotation_y(std::f32::consts::PI),
-                South => Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2),
-            },
-            ..default()
-        }
-    }
-}
-
-impl ModuleRotation {
-    pub fn next_cw(self) -> Self {
-        use ModuleRotation::*;
-        match self {
-            East => South,
-            North => East,
-            West => North,
-            South => West,
-        }
-    }
-
-    pub fn next_ccw(self) -> Self {
-        use ModuleRotation::*;
-        match self {
-            East => North,
-            North => West,
-            West => South,
-            South => East,
-        }
-    }
-
-    pub fn rotate_index(self, index: IVec2) -> IVec2 {
-        use ModuleRotation::*;
-        match self {
-            East => IVec2::new(index.x, index.y),
-            North => IVec2::new(-index.y, index.x),
-            West => IVec2::new(-index.x, -index.y),
-            South => IVec2::new(index.y, -index.x),
+            self.bound.min.x += 1;
         }
     }
 }
@@ -265,16 +195,6 @@ impl ShipModuleGridSpaces {
     }
 }
 
-/// Shorthand for constructing [ShipModuleGridSpaces]
-#[macro_export]
-macro_rules! grid_spaces {
-    [$(($x:expr, $y:expr)),* $(,)?] => {
-        crate::modules::grid::ShipModuleGridSpaces {
-            spaces: vec![$(bevy::math::IVec2::new($x, $y)),*],
-        }
-    };
-}
-
 /// When on an entity with a [GlobalTransform] will be updated with the current grid index and module
 #[derive(Component, Default)]
 pub struct ShipGridPresence {
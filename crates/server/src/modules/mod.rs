@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 
 pub mod atmosphere;
+pub mod connections;
+pub mod construction;
 pub mod grid;
 pub mod module_types;
 pub mod networking;
@@ -9,5 +11,7 @@ pub fn build(app: &mut App) {
     grid::build(app);
     module_types::build(app);
     networking::build(app);
+    connections::build(app);
     atmosphere::build(app);
+    construction::build(app);
 }
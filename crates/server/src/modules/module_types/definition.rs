@@ -0,0 +1,303 @@
+use std::{path::PathBuf, sync::Arc};
+
+use bevy::prelude::*;
+use common::{elements::door::DOOR_SIZE, mesh_colliders::GltfCollider};
+use serde::Deserialize;
+
+use crate::{
+    elements::{door::Door, room_vent::RoomVent, ship_map::ShipMapBundle, tank::Tank},
+    modules::{
+        atmosphere::{AtmosphereGate, ModuleAtmosphere, ModuleVent, TankAtmosphere},
+        connections::DockingPort,
+        grid::{ShipModuleGridSpaces, ShipModuleTransform, SHIP_GRID_SCALE},
+        networking::ModuleAssets,
+    },
+    player::spawn::SpawnPoint,
+};
+
+use super::{add_ship_module_type_with_spawner, InitShipModules, ShipModuleDescription};
+
+pub fn build(app: &mut App) {
+    for definition in load_ship_module_definitions() {
+        add_ship_module_definition(app, definition);
+    }
+
+    app.add_systems(Update, init_defined_modules.in_set(InitShipModules));
+}
+
+/// A ship module type described by an asset file in `assets/ship_modules/definitions`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShipModuleDefinition {
+    /// name of the module type, used by scenarios and saves
+    pub name: String,
+    /// name of the module's files in the `meshes`, `colliders` and `map` folders of `assets/ship_modules`
+    pub assets: String,
+    pub grid_spaces: Vec<IVec2>,
+    /// ports that link the module to adjacent modules
+    #[serde(default)]
+    pub ports: Vec<DockingPort>,
+    #[serde(default)]
+    pub map_offset: Vec2,
+    pub map_size: Vec2,
+    /// how many grid spaces of atmosphere the module holds
+    pub atmosphere_volume: f32,
+    /// whether the module has a vent to fill it from tanks
+    #[serde(default = "default_vent")]
+    pub vent: bool,
+    #[serde(default)]
+    pub elements: Vec<ModuleElementDefinition>,
+}
+
+fn default_vent() -> bool {
+    true
+}
+
+/// An element spawned as a child of the module.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleElementDefinition {
+    pub element: ModuleElement,
+    #[serde(default)]
+    pub translation: Vec3,
+    /// euler angles in degrees, applied in YXZ order
+    #[serde(default)]
+    pub rotation: Vec3,
+}
+
+#[derive(Deserialize)]
+pub enum ModuleElement {
+    ShipMap,
+    /// a tank that starts full and disabled
+    Tank {
+        volume: f32,
+    },
+    /// a vent control for the module's vent
+    RoomVent,
+    /// a door that starts closed on the edge of a docking port,
+    /// the element's transform is relative to the bottom middle of the port
+    Door {
+        port: DockingPort,
+    },
+    /// where players spawn and respawn
+    SpawnPoint,
+}
+
+impl ModuleElementDefinition {
+    fn transform(&self) -> Transform {
+        let rotation = self.rotation * std::f32::consts::PI / 180.;
+
+        let transform = Transform::from_translation(self.translation).with_rotation(
+            Quat::from_euler(EulerRot::YXZ, rotation.y, rotation.x, rotation.z),
+        );
+
+        match self.element {
+            ModuleElement::Door { port } => door_transform(port) * transform,
+            _ => transform,
+        }
+    }
+}
+
+/// Transform of a door in the middle of a docking port's edge facing the port's direction.
+fn door_transform(port: DockingPort) -> Transform {
+    let position = (port.space.as_vec2() + port.direction.as_vec2() * 0.5) * SHIP_GRID_SCALE;
+    let direction = Vec3::new(port.direction.x as f32, 0., port.direction.y as f32);
+
+    Transform::from_xyz(position.x, DOOR_SIZE.y / 2., position.y)
+        .with_rotation(Quat::from_rotation_arc(Vec3::Z, direction))
+}
+
+/// Inserted on modules spawned from a [ShipModuleDefinition].
+#[derive(Component)]
+pub struct DefinedShipModule {
+    pub definition: Arc<ShipModuleDefinition>,
+}
+
+/// Reads every module definition, logging errors for invalid files.
+fn load_ship_module_definitions() -> Vec<ShipModuleDefinition> {
+    let directory = PathBuf::from(crate::ASSET_PATH).join("ship_modules/definitions");
+
+    let entries = match std::fs::read_dir(&directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            error!(
+                "couldn't read ship module definitions {:?}: {}",
+                directory, err
+            );
+            return Vec::new();
+        }
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .collect();
+
+    // sort so that module type ids don't depend on the directory order
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|file| ron::from_str(&file).map_err(|err| err.to_string()))
+            {
+                Ok(definition) => Some(definition),
+                Err(err) => {
+                    error!("invalid ship module definition {:?}: {}", path, err);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Adds a module type from a definition.
+///
+/// Logs an error and returns `None` if the definition is invalid.
+pub fn add_ship_module_definition(
+    app: &mut App,
+    definition: ShipModuleDefinition,
+) -> Option<super::ShipModuleTypeId> {
+    if definition.grid_spaces.is_empty() {
+        error!(
+            "ship module \"{}\" doesn't have any grid spaces",
+            definition.name
+        );
+        return None;
+    }
+
+    if !definition.atmosphere_volume.is_finite() || definition.atmosphere_volume <= 0. {
+        error!(
+            "ship module \"{}\" has an atmosphere volume of {}, it must be positive and finite",
+            definition.name, definition.atmosphere_volume
+        );
+        return None;
+    }
+
+    if app
+        .world()
+        .resource::<super::ShipModuleTypes>()
+        .find(&definition.name)
+        .is_some()
+    {
+        error!(
+            "ship module \"{}\" was defined more than once, only the first is used",
+            definition.name
+        );
+        return None;
+    }
+
+    let definition = Arc::new(definition);
+
+    let ports = definition
+        .ports
+        .iter()
+        .copied()
+        .filter(|port| {
+            let valid = port.is_valid() && definition.grid_spaces.contains(&port.space);
+
+            if !valid {
+                error!(
+                    "invalid docking port {:?} on ship module \"{}\"",
+                    port, definition.name
+                );
+            }
+
+            valid
+        })
+        .collect();
+
+    let description = ShipModuleDescription {
+        grid_spaces: ShipModuleGridSpaces {
+            spaces: definition.grid_spaces.clone(),
+        },
+        module_name: definition.name.clone(),
+        ports,
+        assets: definition.assets.clone(),
+        map_size: definition.map_size,
+    };
+
+    Some(add_ship_module_type_with_spawner(
+        app,
+        description,
+        move |mut commands| {
+            commands.insert(DefinedShipModule {
+                definition: definition.clone(),
+            });
+        },
+    ))
+}
+
+fn init_defined_modules(
+    mut commands: Commands,
+    module_q: Query<(Entity, &DefinedShipModule, &ShipModuleTransform), Added<DefinedShipModule>>,
+    assets: Res<AssetServer>,
+) {
+    for (module_entity, DefinedShipModule { definition }, &module_transform) in module_q.iter() {
+        let mesh = assets.load(format!("ship_modules/colliders/{}.gltf", definition.assets));
+
+        commands.entity(module_entity).insert((
+            ModuleAssets {
+                path: definition.assets.clone(),
+                map_offset: definition.map_offset,
+                map_size: definition.map_size,
+            },
+            GltfCollider { mesh },
+            ModuleAtmosphere {
+                volume: definition.atmosphere_volume,
+                level: definition.atmosphere_volume,
+                breached: false,
+            },
+        ));
+
+        if definition.vent {
+            commands
+                .entity(module_entity)
+                .insert(ModuleVent { open: true });
+        }
+
+        for element in definition.elements.iter() {
+            let transform = element.transform();
+
+            let mut element_commands = match element.element {
+                ModuleElement::ShipMap => commands.spawn(ShipMapBundle {
+                    transform,
+                    ..default()
+                }),
+                ModuleElement::Tank { volume } => commands.spawn((
+                    Tank,
+                    TankAtmosphere {
+                        volume,
+                        level: volume,
+                        enabled: false,
+                    },
+                    transform,
+                )),
+                ModuleElement::RoomVent => commands.spawn((RoomVent { module_entity }, transform)),
+                ModuleElement::SpawnPoint => commands.spawn((SpawnPoint, transform)),
+                ModuleElement::Door { port } => {
+                    if !port.is_valid() || !definition.grid_spaces.contains(&port.space) {
+                        error!(
+                            "invalid door port {:?} on ship module \"{}\"",
+                            port, definition.name
+                        );
+                        continue;
+                    }
+
+                    commands.spawn((
+                        Door { open: false },
+                        AtmosphereGate {
+                            port: port.transformed(module_transform),
+                            open: false,
+                        },
+                        transform,
+                    ))
+                }
+            };
+
+            element_commands.set_parent(module_entity);
+        }
+    }
+}
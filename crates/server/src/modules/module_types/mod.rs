@@ -1,9 +1,11 @@
 use bevy::{ecs::system::EntityCommands, prelude::*};
 
-use super::grid::{ShipModuleGrid, ShipModuleGridSpaces, ShipModuleTransform};
+use super::{
+    connections::{DockingPort, ShipModuleConnections},
+    grid::{ShipGridPresence, ShipModuleGrid, ShipModuleGridSpaces, ShipModuleTransform},
+};
 
-pub mod command_module;
-pub mod oxygen_storage_a;
+pub mod definition;
 
 /// System set where ship module initialization
 /// systems should be placed during [Update].
@@ -12,31 +14,47 @@ pub mod oxygen_storage_a;
 #[derive(SystemSet, Hash, PartialEq, Eq, Debug, Clone)]
 pub struct InitShipModules;
 
+/// System set during [Update] where [SpawnShipModule] and [DespawnShipModule] events are handled.
+#[derive(SystemSet, Hash, PartialEq, Eq, Debug, Clone)]
+pub struct SpawnShipModules;
+
 pub fn build(app: &mut App) {
     app.add_event::<SpawnShipModule>();
+    app.add_event::<DespawnShipModule>();
 
     app.init_resource::<ShipModuleTypes>();
 
-    command_module::build(app);
-    oxygen_storage_a::build(app);
+    definition::build(app);
 
-    app.add_systems(Update, spawn_ship_modules.before(InitShipModules));
+    app.add_systems(
+        Update,
+        (despawn_ship_modules, spawn_ship_modules)
+            .chain()
+            .in_set(SpawnShipModules)
+            .before(InitShipModules),
+    );
 }
 
-/// Adds a module type to the app.
-pub fn add_ship_module_type<M: Component + Default>(
+/// Adds a module type to the app that calls `spawner` on each new module entity.
+pub fn add_ship_module_type_with_spawner(
     app: &mut App,
     description: ShipModuleDescription,
+    spawner: impl Fn(EntityCommands) + Send + Sync + 'static,
 ) -> ShipModuleTypeId {
     let mut modules = app.world_mut().resource_mut::<ShipModuleTypes>();
 
+    if modules.find(&description.module_name).is_some() {
+        warn!(
+            "Ship module type \"{}\" was added more than once, saves and scenarios will use the first",
+            description.module_name
+        );
+    }
+
     let id = ShipModuleTypeId(modules.module_descriptions.len());
 
     modules.module_descriptions.push(ShipModuleType {
         description,
-        spawner: Box::new(|mut commands: EntityCommands| {
-            commands.insert(M::default());
-        }),
+        spawner: Box::new(spawner),
     });
 
     id
@@ -48,6 +66,46 @@ pub struct ShipModuleTypes {
     module_descriptions: Vec<ShipModuleType>,
 }
 
+impl ShipModuleTypes {
+    /// Gets the name of a module type.
+    pub fn name(&self, module_type_id: ShipModuleTypeId) -> Option<&str> {
+        self.module_descriptions
+            .get(module_type_id.0)
+            .map(|module_type| module_type.description.module_name.as_str())
+    }
+
+    /// Gets the grid spaces of a module type.
+    pub fn grid_spaces(&self, module_type_id: ShipModuleTypeId) -> Option<&ShipModuleGridSpaces> {
+        self.module_descriptions
+            .get(module_type_id.0)
+            .map(|module_type| &module_type.description.grid_spaces)
+    }
+
+    /// Gets the docking ports of a module type, empty if the id is invalid.
+    pub fn ports(&self, module_type_id: ShipModuleTypeId) -> &[DockingPort] {
+        self.module_descriptions
+            .get(module_type_id.0)
+            .map(|module_type| &module_type.description.ports[..])
+            .unwrap_or(&[])
+    }
+
+    /// Iterates over every module type and it's description.
+    pub fn iter(&self) -> impl Iterator<Item = (ShipModuleTypeId, &ShipModuleDescription)> {
+        self.module_descriptions
+            .iter()
+            .enumerate()
+            .map(|(index, module_type)| (ShipModuleTypeId(index), &module_type.description))
+    }
+
+    /// Finds a module type by it's name.
+    pub fn find(&self, module_name: &str) -> Option<ShipModuleTypeId> {
+        self.module_descriptions
+            .iter()
+            .position(|module_type| module_type.description.module_name == module_name)
+            .map(ShipModuleTypeId)
+    }
+}
+
 pub struct ShipModuleType {
     description: ShipModuleDescription,
     spawner: Box<dyn Fn(EntityCommands) + Send + Sync + 'static>,
@@ -57,6 +115,11 @@ pub struct ShipModuleType {
 pub struct ShipModuleDescription {
     pub grid_spaces: ShipModuleGridSpaces,
     pub module_name: String,
+    /// ports that link the module to adjacent modules, before the module is rotated
+    pub ports: Vec<DockingPort>,
+    /// name of the module's files in `assets/ship_modules`, sent to clients in the catalog
+    pub assets: String,
+    pub map_size: Vec2,
 }
 
 /// Exists on every ship module.
@@ -71,15 +134,36 @@ pub struct ShipModule {
 #[derive(Clone, Copy, Debug)]
 pub struct ShipModuleTypeId(usize);
 
+impl ShipModuleTypeId {
+    /// The id as it is sent to clients in the [ShipModuleCatalog](common::modules::ShipModuleCatalog).
+    pub fn to_u32(self) -> u32 {
+        self.0 as u32
+    }
+}
+
 /// Fire this event to spawn a ship module.
 ///
 /// Will log an error if the module doesn't fit and do nothing.
+///
+/// Attachment to the rest of the ship isn't checked, player placement is validated in [construction](super::construction).
 #[derive(Event)]
 pub struct SpawnShipModule {
     pub module_type_id: ShipModuleTypeId,
     pub transform: ShipModuleTransform,
 }
 
+/// Fire this event to remove a ship module.
+///
+/// Frees the module's grid spaces and despawns it along with it's elements,
+/// clients are told to unload it by despawn replication.
+///
+/// Logs a warning and does nothing if removing the module would split the ship, unless `allow_split` is set.
+#[derive(Event)]
+pub struct DespawnShipModule {
+    pub module_entity: Entity,
+    pub allow_split: bool,
+}
+
 fn spawn_ship_modules(
     mut commands: Commands,
     mut spawn_module_r: EventReader<SpawnShipModule>,
@@ -91,40 +175,130 @@ fn spawn_ship_modules(
         transform,
     } in spawn_module_r.read()
     {
-        let Some(ShipModuleType {
-            description,
-            spawner,
-        }) = modules.module_descriptions.get(module_type_id.0)
-        else {
-            error!("Ship module type id out of range {:?}", module_type_id);
+        spawn_ship_module(
+            &mut commands,
+            modules.as_ref(),
+            grid.as_mut(),
+            module_type_id,
+            transform,
+        );
+    }
+}
+
+/// Spawns a ship module and inserts it into the grid.
+///
+/// Logs an error and returns `None` if the module doesn't fit.
+pub fn spawn_ship_module(
+    commands: &mut Commands,
+    modules: &ShipModuleTypes,
+    grid: &mut ShipModuleGrid,
+    module_type_id: ShipModuleTypeId,
+    transform: ShipModuleTransform,
+) -> Option<Entity> {
+    let Some(ShipModuleType {
+        description,
+        spawner,
+    }) = modules.module_descriptions.get(module_type_id.0)
+    else {
+        error!("Ship module type id out of range {:?}", module_type_id);
+        return None;
+    };
+
+    let true = description.grid_spaces.fits_in_grid(transform, grid) else {
+        error!("Tried to place ship module but it doesn't fit");
+        return None;
+    };
+
+    let module_entity = commands
+        .spawn((
+            ShipModule { module_type_id },
+            transform,
+            transform.to_world_transform(),
+        ))
+        .id();
+
+    spawner(commands.entity(module_entity));
+
+    description
+        .grid_spaces
+        .insert_into_grid(transform, module_entity, grid);
+
+    debug!(
+        "Spawned ship module {} \"{}\" at {} {:?}",
+        module_entity, description.module_name, transform.translation, transform.rotation
+    );
+
+    Some(module_entity)
+}
+
+fn despawn_ship_modules(
+    mut commands: Commands,
+    mut despawn_module_r: EventReader<DespawnShipModule>,
+    module_q: Query<(&ShipModule, &ShipModuleTransform)>,
+    mut presence_q: Query<&mut ShipGridPresence>,
+    modules: Res<ShipModuleTypes>,
+    mut grid: ResMut<ShipModuleGrid>,
+    mut connections: ResMut<ShipModuleConnections>,
+) {
+    let mut removed_any = false;
+
+    for &DespawnShipModule {
+        module_entity,
+        allow_split,
+    } in despawn_module_r.read()
+    {
+        let Ok((module, &transform)) = module_q.get(module_entity) else {
+            error!(
+                "Tried to despawn {} but it isn't a ship module",
+                module_entity
+            );
             continue;
         };
 
-        let true = description
-            .grid_spaces
-            .fits_in_grid(transform, grid.as_ref())
+        let Some(ShipModuleType { description, .. }) =
+            modules.module_descriptions.get(module.module_type_id.0)
         else {
-            error!("Tried to place ship module but it doesn't fit");
+            error!(
+                "Ship module type id out of range {:?}",
+                module.module_type_id
+            );
             continue;
         };
 
-        let module_entity = commands
-            .spawn((
-                ShipModule { module_type_id },
-                transform,
-                transform.to_world_transform(),
-            ))
-            .id();
+        if !allow_split && connections.would_split(module_entity) {
+            warn!(
+                "Didn't despawn ship module {} \"{}\" because it would split the ship",
+                module_entity, description.module_name
+            );
+            continue;
+        }
+
+        connections.remove_module(module_entity);
+
+        for space in description.grid_spaces.spaces_transformed(transform) {
+            if grid.get(space) == Some(module_entity) {
+                grid.remove(space);
+            }
+        }
 
-        spawner(commands.entity(module_entity));
+        removed_any = true;
 
-        description
-            .grid_spaces
-            .insert_into_grid(transform, module_entity, grid.as_mut());
+        // players in the module are no longer in any module until their presence is next updated
+        for mut presence in presence_q.iter_mut() {
+            if presence.current_module == Some(module_entity) {
+                presence.current_module = None;
+            }
+        }
+
+        commands.entity(module_entity).despawn_recursive();
 
         debug!(
-            "Spawned ship module {} \"{}\" at {} {:?}",
-            module_entity, description.module_name, transform.translation, transform.rotation
+            "Despawned ship module {} \"{}\"",
+            module_entity, description.module_name
         );
     }
+
+    if removed_any {
+        grid.trim();
+    }
 }
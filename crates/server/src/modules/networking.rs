@@ -1,12 +1,24 @@
 use bevy::prelude::*;
-use common::modules::LoadModule;
+use common::modules::{LoadModule, ShipModuleCatalog, ShipModuleCatalogEntry};
 
 use crate::{networking::prelude::*, state::ReceiveGameUpdates};
 
+use super::module_types::ShipModuleTypes;
+
 pub fn build(app: &mut App) {
-    app.add_plugins(MessageQueuePlugin::<LoadModuleMessageQueue>::default());
+    app.add_plugins((
+        MessageQueuePlugin::<LoadModuleMessageQueue>::default(),
+        DespawnReplicationPlugin::<LoadModuleMessageQueue>::default(),
+    ));
 
-    app.add_systems(Update, (init_new_modules, init_existing_modules));
+    app.add_systems(
+        Update,
+        (
+            send_module_catalog.before(init_existing_modules),
+            init_new_modules,
+            init_existing_modules,
+        ),
+    );
 }
 
 /// When inserted on an entity, the given scene
@@ -15,8 +27,9 @@ pub fn build(app: &mut App) {
 /// The transform of the scene must be inserted before or at
 /// insertion of this component and shouldn't move.
 #[derive(Component)]
+#[require(ReplicateDespawn<LoadModuleMessageQueue>)]
 pub struct ModuleAssets {
-    pub path: &'static str,
+    pub path: String,
     pub map_offset: Vec2,
     pub map_size: Vec2,
 }
@@ -24,6 +37,39 @@ pub struct ModuleAssets {
 /// Marker for the stream for load module messages
 pub struct LoadModuleMessageQueue;
 
+/// Sends the catalog of module types to clients when they join.
+fn send_module_catalog(
+    client_q: Query<Entity, Added<ReceiveGameUpdates>>,
+    modules: Res<ShipModuleTypes>,
+    mut messages: QueuedMessageSender<LoadModuleMessageQueue>,
+    message_id: Res<MessageId<ShipModuleCatalog>>,
+) {
+    if client_q.is_empty() {
+        return;
+    }
+
+    let module_types: Vec<_> = modules
+        .iter()
+        .map(|(module_type_id, description)| ShipModuleCatalogEntry {
+            id: module_type_id.to_u32(),
+            module_name: description.module_name.clone(),
+            grid_spaces: description.grid_spaces.spaces.clone(),
+            asset_path: description.assets.clone(),
+            map_size: description.map_size,
+        })
+        .collect();
+
+    for client_entity in client_q.iter() {
+        messages.send(
+            *message_id,
+            client_entity,
+            ShipModuleCatalog {
+                module_types: module_types.clone(),
+            },
+        );
+    }
+}
+
 /// Responsible for telling clients about new modules.
 fn init_new_modules(
     module_q: Query<(Entity, &ModuleAssets, &Transform), Added<ModuleAssets>>,
@@ -37,7 +83,7 @@ fn init_new_modules(
                 *message_id,
                 client_entity,
                 LoadModule {
-                    path: assets.path.into(),
+                    path: assets.path.clone(),
                     server_entity: scene_entity.into(),
                     translation: transform.translation,
                     rotation: transform.rotation.to_euler(EulerRot::YXZ).0,
@@ -62,7 +108,7 @@ fn init_existing_modules(
                 *message_id,
                 client_entity,
                 LoadModule {
-                    path: assets.path.into(),
+                    path: assets.path.clone(),
                     server_entity: scene_entity.into(),
                     translation: transform.translation,
                     rotation: transform.rotation.to_euler(EulerRot::YXZ).0,
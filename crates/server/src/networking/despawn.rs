@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use common::DespawnEntity;
+
+use super::{message_queue::QueuedMessageSender, messages::MessageId};
+use crate::state::ReceiveGameUpdates;
+
+/// adds despawn replication for entities with a [ReplicateDespawn<S>]
+///
+/// [DespawnEntity] messages are sent on the `S` message queue
+pub struct DespawnReplicationPlugin<S>(PhantomData<S>);
+
+impl<S> Default for DespawnReplicationPlugin<S> {
+    fn default() -> Self {
+        DespawnReplicationPlugin(PhantomData)
+    }
+}
+
+impl<S: Send + Sync + 'static> Plugin for DespawnReplicationPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, replicate_despawns::<S>);
+    }
+}
+
+/// when an entity with this component is despawned, or the component is removed,
+/// clients receiving game updates are told to despawn it
+///
+/// `S` should be the message queue the entity was initialized on
+/// so that clients never receive the despawn before the entity
+#[derive(Component)]
+pub struct ReplicateDespawn<S: Send + Sync + 'static> {
+    _p: PhantomData<S>,
+}
+
+impl<S: Send + Sync + 'static> Default for ReplicateDespawn<S> {
+    fn default() -> Self {
+        ReplicateDespawn { _p: PhantomData }
+    }
+}
+
+fn replicate_despawns<S: Send + Sync + 'static>(
+    mut despawned: RemovedComponents<ReplicateDespawn<S>>,
+    client_q: Query<Entity, With<ReceiveGameUpdates>>,
+    mut messages: QueuedMessageSender<S>,
+    message_id: Res<MessageId<DespawnEntity>>,
+) {
+    for entity in despawned.read() {
+        debug!("replicating despawn of {}", entity);
+
+        for client_entity in client_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                DespawnEntity {
+                    entity: entity.into(),
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use common::networking::{fingerprint::ProtocolFingerprint, ProtocolHandshake, ProtocolRejected};
+use nevy::prelude::ReceivedMessages;
+
+use super::{
+    message_queue::{MessageQueuePlugin, QueuedMessageSender},
+    messages::MessageId,
+    ClientConnection,
+};
+
+pub fn build(app: &mut App) {
+    app.insert_resource(ProtocolFingerprint::compute());
+
+    app.add_plugins(MessageQueuePlugin::<HandshakeQueue>::default());
+
+    app.add_systems(Startup, log_protocol_fingerprint);
+    app.add_systems(Update, receive_protocol_handshakes);
+}
+
+/// inserted on a client once it has sent a [ProtocolHandshake] with a matching fingerprint
+///
+/// clients without this component can't join
+#[derive(Component)]
+pub struct ProtocolVerified;
+
+/// marker for the handshake message queue
+struct HandshakeQueue;
+
+fn log_protocol_fingerprint(fingerprint: Res<ProtocolFingerprint>) {
+    info!("Protocol fingerprint {}", *fingerprint);
+}
+
+fn receive_protocol_handshakes(
+    mut commands: Commands,
+    mut client_q: Query<(Entity, &mut ReceivedMessages<ProtocolHandshake>), With<ClientConnection>>,
+    fingerprint: Res<ProtocolFingerprint>,
+    mut messages: QueuedMessageSender<HandshakeQueue>,
+    message_id: Res<MessageId<ProtocolRejected>>,
+) {
+    for (client_entity, mut handshakes) in client_q.iter_mut() {
+        for ProtocolHandshake {
+            fingerprint: client_fingerprint,
+        } in handshakes.drain()
+        {
+            if client_fingerprint == *fingerprint {
+                debug!("client {} verified it's protocol", client_entity);
+
+                commands.entity(client_entity).insert(ProtocolVerified);
+                continue;
+            }
+
+            warn!(
+                "client {} has protocol {} but the server has {}",
+                client_entity, client_fingerprint, *fingerprint
+            );
+
+            commands.entity(client_entity).remove::<ProtocolVerified>();
+
+            messages.send(
+                *message_id,
+                client_entity,
+                ProtocolRejected {
+                    server_fingerprint: *fingerprint,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+use bevy::prelude::*;
+use common::networking::certificate_fingerprint;
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+
+/// directory the server identity is stored in, relative to the working directory
+const IDENTITY_DIRECTORY: &str = "server_identity";
+const CERTIFICATE_FILE: &str = "cert.pem";
+const PRIVATE_KEY_FILE: &str = "key.pem";
+
+/// names the generated self signed certificate is valid for
+const SELF_SIGNED_NAMES: &[&str] = &["dev.shipgame", "localhost"];
+
+/// the certificate chain and private key the server uses for tls
+///
+/// stored on disk so that clients can pin the certificate,
+/// a self signed certificate is generated on first run.
+/// to use a certificate signed by a CA replace both files
+pub struct ServerIdentity {
+    pub certificate_chain: Vec<CertificateDer<'static>>,
+    pub private_key: PrivateKeyDer<'static>,
+}
+
+impl ServerIdentity {
+    pub fn load_or_generate() -> Result<Self, String> {
+        let directory = Path::new(IDENTITY_DIRECTORY);
+        let certificate_path = directory.join(CERTIFICATE_FILE);
+        let private_key_path = directory.join(PRIVATE_KEY_FILE);
+
+        match (certificate_path.exists(), private_key_path.exists()) {
+            (true, true) => (),
+            (false, false) => {
+                info!(
+                    "No server identity found, generating a self signed certificate in {:?}",
+                    directory
+                );
+
+                let certificate = rcgen::generate_simple_self_signed(
+                    SELF_SIGNED_NAMES
+                        .iter()
+                        .map(|name| name.to_string())
+                        .collect::<Vec<_>>(),
+                )
+                .map_err(|err| format!("failed to generate certificate: {}", err))?;
+
+                std::fs::create_dir_all(directory)
+                    .map_err(|err| format!("failed to create {:?}: {}", directory, err))?;
+                std::fs::write(&certificate_path, certificate.cert.pem())
+                    .map_err(|err| format!("failed to write {:?}: {}", certificate_path, err))?;
+                write_private_key(&private_key_path, &certificate.key_pair.serialize_pem())
+                    .map_err(|err| format!("failed to write {:?}: {}", private_key_path, err))?;
+            }
+            _ => {
+                return Err(format!(
+                    "only one of {:?} and {:?} exists, restore the missing file or remove both to generate a new identity",
+                    certificate_path, private_key_path
+                ));
+            }
+        }
+
+        let certificate_chain = CertificateDer::pem_file_iter(&certificate_path)
+            .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| format!("failed to read {:?}: {}", certificate_path, err))?;
+
+        let Some(certificate) = certificate_chain.first() else {
+            return Err(format!("{:?} contains no certificates", certificate_path));
+        };
+
+        let private_key = PrivateKeyDer::from_pem_file(&private_key_path)
+            .map_err(|err| format!("failed to read {:?}: {}", private_key_path, err))?;
+
+        info!(
+            "Server certificate fingerprint {}",
+            certificate_fingerprint(certificate)
+        );
+
+        Ok(ServerIdentity {
+            certificate_chain,
+            private_key,
+        })
+    }
+}
+
+/// Writes the private key so that only the server's user can read it on unix.
+fn write_private_key(path: &Path, private_key: &str) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options.open(path)?.write_all(private_key.as_bytes())
+}
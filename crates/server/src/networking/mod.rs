@@ -4,10 +4,15 @@ use bevy::prelude::*;
 use common::networking::*;
 use nevy::prelude::*;
 
+pub mod despawn;
+pub mod handshake;
+pub mod identity;
 pub mod message_queue;
 pub mod messages;
 
 pub mod prelude {
+    pub use super::despawn::{DespawnReplicationPlugin, ReplicateDespawn};
+    pub use super::handshake::ProtocolVerified;
     pub use super::message_queue::{MessageQueuePlugin, QueuedMessageSender};
     pub use super::messages::{MessageId, MessageSender};
     pub use super::{ClientConnected, ClientConnection, ClientDisconnected};
@@ -24,6 +29,8 @@ pub fn build(app: &mut App) {
     app.add_event::<ClientConnected>();
     app.add_event::<ClientDisconnected>();
 
+    handshake::build(app);
+
     app.add_systems(Startup, spawn_endpoint);
     app.add_systems(
         PreUpdate,
@@ -137,9 +144,8 @@ fn on_disconnections(
 }
 
 fn create_server_endpoint_config() -> nevy::quic::quinn_proto::ServerConfig {
-    let cert = rcgen::generate_simple_self_signed(vec!["dev.shipgame".to_string()]).unwrap();
-    let key = rustls::pki_types::PrivateKeyDer::try_from(cert.key_pair.serialize_der()).unwrap();
-    let chain = cert.cert.der().clone();
+    let identity = identity::ServerIdentity::load_or_generate()
+        .unwrap_or_else(|err| panic!("Couldn't load the server identity: {}", err));
 
     let mut tls_config = rustls::ServerConfig::builder_with_provider(std::sync::Arc::new(
         rustls::crypto::ring::default_provider(),
@@ -147,8 +153,8 @@ fn create_server_endpoint_config() -> nevy::quic::quinn_proto::ServerConfig {
     .with_protocol_versions(&[&rustls::version::TLS13])
     .unwrap()
     .with_no_client_auth()
-    .with_single_cert(vec![chain], key)
-    .unwrap();
+    .with_single_cert(identity.certificate_chain, identity.private_key)
+    .expect("Server identity should be a valid certificate and key");
 
     tls_config.max_early_data_size = u32::MAX;
     tls_config.alpn_protocols = vec![b"h3".to_vec()]; // this one is important
@@ -1,10 +1,26 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
+use common::physics::SimulationTick;
 
 pub mod networking;
 
+/// The simulation, including physics, runs in the fixed schedules at the configured tick interval.
 pub fn build(app: &mut App) {
-    app.add_plugins(PhysicsPlugins::default());
+    let tick_interval = app
+        .world()
+        .resource::<crate::ServerConfig>()
+        .tick_interval();
+    app.insert_resource(Time::<Fixed>::from_duration(tick_interval));
+
+    app.init_resource::<SimulationTick>();
+
+    app.add_plugins(PhysicsPlugins::new(FixedPostUpdate));
+
+    app.add_systems(FixedFirst, advance_simulation_tick);
 
     networking::build(app);
 }
+
+fn advance_simulation_tick(mut tick: ResMut<SimulationTick>) {
+    tick.0 += 1;
+}
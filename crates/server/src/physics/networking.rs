@@ -1,18 +1,23 @@
-use std::time::Duration;
-
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{prelude::*, time::common_conditions::on_timer};
 
 use crate::networking::prelude::*;
 use crate::state::ReceiveGameUpdates;
 
 use common::physics::*;
 
-pub const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(150);
-pub const TIME_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
-
 pub fn build(app: &mut App) {
-    app.add_systems(Update, (send_time_samples, send_physics_snapshots));
+    let server_config = app.world().resource::<crate::ServerConfig>();
+    let time_sample_interval = server_config.time_sample_interval();
+    let snapshot_interval = server_config.snapshot_interval();
+
+    app.add_systems(
+        FixedLast,
+        (
+            send_time_samples.run_if(on_timer(time_sample_interval)),
+            send_physics_snapshots.run_if(on_timer(snapshot_interval)),
+        ),
+    );
 }
 
 /// marker component for which physics bodies to replicate
@@ -24,21 +29,18 @@ fn send_time_samples(
     mut messages: MessageSender,
     message_id: Res<MessageId<TimeSample>>,
     time: Res<Time>,
-    mut last_sample: Local<Duration>,
+    tick: Res<SimulationTick>,
 ) {
-    if time.elapsed() > *last_sample + TIME_SAMPLE_INTERVAL {
-        *last_sample = time.elapsed();
-
-        for client_entity in client_q.iter() {
-            // if out of bandwidth don't send
-            messages.send(
-                *message_id,
-                client_entity,
-                &TimeSample {
-                    time: time.elapsed(),
-                },
-            );
-        }
+    for client_entity in client_q.iter() {
+        // if out of bandwidth don't send
+        messages.send(
+            *message_id,
+            client_entity,
+            &TimeSample {
+                time: time.elapsed(),
+                tick: *tick,
+            },
+        );
     }
 }
 
@@ -48,38 +50,35 @@ fn send_physics_snapshots(
     mut messages: MessageSender,
     message_id: Res<MessageId<PhysicsSnapshot>>,
     time: Res<Time>,
-    mut last_snapshot: Local<Duration>,
+    tick: Res<SimulationTick>,
 ) {
-    if time.elapsed() > *last_snapshot + SNAPSHOT_INTERVAL {
-        *last_snapshot = time.elapsed();
-
-        let snapshot = PhysicsSnapshot {
-            bodies: body_q
-                .iter()
-                .map(
-                    |(
-                        body_entity,
-                        &Position(position),
-                        &LinearVelocity(linear_velocity),
-                        &Rotation(rotation),
-                    )| {
-                        (
-                            body_entity.into(),
-                            PhysicsBodySnapshot {
-                                position,
-                                linear_velocity,
-                                rotation,
-                            },
-                        )
-                    },
-                )
-                .collect(),
-            time: time.elapsed(),
-        };
+    let snapshot = PhysicsSnapshot {
+        bodies: body_q
+            .iter()
+            .map(
+                |(
+                    body_entity,
+                    &Position(position),
+                    &LinearVelocity(linear_velocity),
+                    &Rotation(rotation),
+                )| {
+                    (
+                        body_entity.into(),
+                        PhysicsBodySnapshot {
+                            position,
+                            linear_velocity,
+                            rotation,
+                        },
+                    )
+                },
+            )
+            .collect(),
+        time: time.elapsed(),
+        tick: *tick,
+    };
 
-        for client_entity in client_q.iter() {
-            // if out of bandwidth don't send
-            messages.send(*message_id, client_entity, &snapshot);
-        }
+    for client_entity in client_q.iter() {
+        // if out of bandwidth don't send
+        messages.send(*message_id, client_entity, &snapshot);
     }
 }
@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use common::player::{
+    controller::{MovementMode, PlayerInput, PLAYER_MOVE_SPEED},
+    vitality::PlayerDead,
+    ClientPlayerInput, PlayerInputAck,
+};
+use nevy::prelude::ReceivedMessages;
+
+use super::networking::{ConnectedClient, ConnectedPlayer};
+use crate::networking::prelude::*;
+
+/// Inputs buffered beyond this are dropped, oldest first,
+/// so that a client sending too many inputs can't get ahead of the server.
+const MAX_BUFFERED_INPUTS: usize = 16;
+
+pub fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        receive_player_inputs.run_if(server_authoritative_movement),
+    );
+    app.add_systems(
+        FixedUpdate,
+        apply_player_inputs.run_if(server_authoritative_movement),
+    );
+    app.add_systems(
+        FixedLast,
+        send_input_acks.run_if(server_authoritative_movement),
+    );
+}
+
+/// Run condition for when the server simulates players from their inputs.
+pub fn server_authoritative_movement(server_config: Res<crate::ServerConfig>) -> bool {
+    server_config.movement_mode == MovementMode::ServerAuthoritative
+}
+
+/// [ClientPlayerInput]s waiting to be simulated, one is applied every tick.
+///
+/// Replaced when a client resumes the player because it's sequence numbers start again.
+#[derive(Component, Default)]
+pub struct PlayerInputBuffer {
+    inputs: VecDeque<ClientPlayerInput>,
+    /// sequence number of the newest input received
+    last_received: Option<u32>,
+    /// sequence number of the input applied this tick, acknowledged at the end of the tick
+    unacknowledged: Option<u32>,
+}
+
+fn receive_player_inputs(
+    mut client_q: Query<(
+        Entity,
+        &mut ReceivedMessages<ClientPlayerInput>,
+        Option<&ConnectedPlayer>,
+    )>,
+    mut player_q: Query<&mut PlayerInputBuffer>,
+) {
+    for (client_entity, mut client_messages, connected_player) in client_q.iter_mut() {
+        for message in client_messages.drain() {
+            let Some(connected_player) = connected_player else {
+                warn!(
+                    "client {} sent a player input but they don't have a connected player",
+                    client_entity
+                );
+                continue;
+            };
+
+            let player_entity = connected_player.get();
+
+            let Ok(mut buffer) = player_q.get_mut(player_entity) else {
+                error!(
+                    "couldn't query client {}'s connected player {} to buffer an input",
+                    client_entity, player_entity
+                );
+                continue;
+            };
+
+            if message.input.target_velocity.length() > PLAYER_MOVE_SPEED + f32::EPSILON * 10.
+                || !message.input.look_direction.is_normalized()
+            {
+                warn!(
+                    "rejected invalid input {} for player {} from client {}",
+                    message.sequence, player_entity, client_entity
+                );
+                continue;
+            }
+
+            // inputs are sent unordered, late ones are dropped
+            if buffer
+                .last_received
+                .is_some_and(|last_received| message.sequence <= last_received)
+            {
+                continue;
+            }
+
+            buffer.last_received = Some(message.sequence);
+            buffer.inputs.push_back(message);
+
+            if buffer.inputs.len() > MAX_BUFFERED_INPUTS {
+                buffer.inputs.pop_front();
+            }
+        }
+    }
+}
+
+/// Applies the next buffered input of every player before the player controller runs.
+///
+/// If there isn't an input for this tick the player keeps the previous one.
+fn apply_player_inputs(
+    mut player_q: Query<(&mut PlayerInputBuffer, &mut PlayerInput, Has<PlayerDead>)>,
+) {
+    for (mut buffer, mut player_input, dead) in player_q.iter_mut() {
+        let Some(ClientPlayerInput { sequence, input }) = buffer.inputs.pop_front() else {
+            continue;
+        };
+
+        buffer.unacknowledged = Some(sequence);
+
+        // dead players can't move
+        if dead {
+            continue;
+        }
+
+        *player_input = input;
+    }
+}
+
+/// Tells clients where their player is after the inputs applied this tick.
+fn send_input_acks(
+    mut player_q: Query<(
+        &mut PlayerInputBuffer,
+        &ConnectedClient,
+        &Position,
+        &LinearVelocity,
+    )>,
+    mut messages: MessageSender,
+    message_id: Res<MessageId<PlayerInputAck>>,
+) {
+    for (mut buffer, connected_client, &Position(position), &LinearVelocity(linear_velocity)) in
+        player_q.iter_mut()
+    {
+        let Some(sequence) = buffer.unacknowledged.take() else {
+            continue;
+        };
+
+        // if out of bandwidth don't send, a later ack replaces this one
+        messages.send(
+            *message_id,
+            connected_client.get(),
+            &PlayerInputAck {
+                sequence,
+                position,
+                linear_velocity,
+            },
+        );
+    }
+}
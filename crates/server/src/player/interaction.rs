@@ -0,0 +1,95 @@
+use avian3d::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use common::{
+    player::{vitality::PlayerDead, INTERACTION_DISTANCE, PLAYER_EYE_HEIGHT},
+    GameLayer,
+};
+
+use super::Player;
+
+/// Extra distance allowed on top of [INTERACTION_DISTANCE],
+/// because clients measure to the surface of an element and the server measures to it's origin.
+const INTERACTION_DISTANCE_TOLERANCE: f32 = 1.5;
+/// Distance before an element that the line of sight ray stops,
+/// so that the surface the element is mounted on doesn't block it.
+const LINE_OF_SIGHT_MARGIN: f32 = 0.25;
+
+/// Reason a player isn't allowed to interact with an element.
+#[derive(Debug)]
+pub enum InteractionViolation {
+    /// the player or it's position doesn't exist
+    UnknownPlayer,
+    /// the element doesn't have a position
+    UnknownElement,
+    /// dead players can't interact
+    Dead,
+    /// the element is further away than players can reach
+    OutOfRange { distance: f32 },
+    /// world geometry is between the player and the element
+    Blocked,
+}
+
+/// Checks whether players can reach elements they send requests for.
+#[derive(SystemParam)]
+pub struct PlayerInteraction<'w, 's> {
+    player_q: Query<'w, 's, (&'static Position, Has<PlayerDead>), With<Player>>,
+    element_q: Query<'w, 's, &'static GlobalTransform>,
+    spatial_query: SpatialQuery<'w, 's>,
+    server_config: Res<'w, crate::ServerConfig>,
+}
+
+impl PlayerInteraction<'_, '_> {
+    /// Checks that a player is close enough to an element to interact with it,
+    /// and that it can see the element if `interaction_line_of_sight` is enabled.
+    pub fn check(
+        &self,
+        player_entity: Entity,
+        element_entity: Entity,
+    ) -> Result<(), InteractionViolation> {
+        let Ok((&Position(player_position), dead)) = self.player_q.get(player_entity) else {
+            return Err(InteractionViolation::UnknownPlayer);
+        };
+
+        if dead {
+            return Err(InteractionViolation::Dead);
+        }
+
+        let Ok(element_transform) = self.element_q.get(element_entity) else {
+            return Err(InteractionViolation::UnknownElement);
+        };
+
+        let eye_position = player_position + Vec3::Y * PLAYER_EYE_HEIGHT;
+        let to_element = element_transform.translation() - eye_position;
+        let distance = to_element.length();
+
+        if distance > INTERACTION_DISTANCE + INTERACTION_DISTANCE_TOLERANCE {
+            return Err(InteractionViolation::OutOfRange { distance });
+        }
+
+        if !self.server_config.interaction_line_of_sight || distance <= LINE_OF_SIGHT_MARGIN {
+            return Ok(());
+        }
+
+        let Ok(direction) = Dir3::new(to_element) else {
+            return Ok(());
+        };
+
+        let blocked = self
+            .spatial_query
+            .cast_ray(
+                eye_position,
+                direction,
+                distance - LINE_OF_SIGHT_MARGIN,
+                true,
+                &SpatialQueryFilter::from_mask([GameLayer::World])
+                    .with_excluded_entities([element_entity]),
+            )
+            .is_some();
+
+        if blocked {
+            return Err(InteractionViolation::Blocked);
+        }
+
+        Ok(())
+    }
+}
@@ -9,16 +9,25 @@ use common::{
     GameLayer,
 };
 
-use crate::{modules::grid::ShipGridPresence, physics::networking::ReplicateBody};
+use crate::{
+    modules::grid::ShipGridPresence, networking::prelude::ReplicateDespawn,
+    physics::networking::ReplicateBody,
+};
 
+pub mod input;
+pub mod interaction;
 pub mod networking;
+pub mod spawn;
+pub mod validation;
 pub mod vitality;
 
 pub fn build(app: &mut App) {
     networking::build(app);
+    input::build(app);
     vitality::build(app);
+    spawn::build(app);
 
-    build_player_controller(app);
+    build_player_controller(app, FixedPostUpdate);
 
     app.add_systems(PostUpdate, update_player_transform);
 }
@@ -28,6 +37,7 @@ pub fn build(app: &mut App) {
 /// the player entity is separate from the
 /// client entity and can exist without one
 #[derive(Component)]
+#[require(ReplicateDespawn<networking::PlayerUpdateQueue>)]
 pub struct Player {
     pub username: String,
 }
@@ -45,14 +55,16 @@ struct PlayerBundle {
     collision_layers: CollisionLayers,
     grid_presence: ShipGridPresence,
     vitality: PlayerVitality,
+    update_validation: validation::PlayerUpdateValidation,
+    input_buffer: input::PlayerInputBuffer,
 }
 
 impl PlayerBundle {
-    pub fn new(username: String) -> Self {
+    pub fn new(username: String, position: Vec3) -> Self {
         PlayerBundle {
             player: Player { username },
             player_input: PlayerInput::default(),
-            position: Position(Vec3::new(0., 1., 0.)),
+            position: Position(position),
             rotation: Rotation::default(),
             transform: Transform::default(),
             linear_velocity: LinearVelocity::default(),
@@ -61,6 +73,8 @@ impl PlayerBundle {
             collision_layers: CollisionLayers::new([GameLayer::Players], 0),
             grid_presence: ShipGridPresence::default(),
             vitality: PlayerVitality::default(),
+            update_validation: default(),
+            input_buffer: default(),
         }
     }
 }
@@ -1,14 +1,41 @@
+use std::time::Duration;
+
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use common::{player::*, state::JoinRequest};
-use controller::PlayerInput;
+use common::{
+    player::*,
+    state::{
+        valid_username, JoinAccepted, JoinRejectReason, JoinRejected, JoinRequest, ResumeToken,
+    },
+};
+use controller::{MovementMode, PlayerInput};
 use nevy::prelude::ReceivedMessages;
+use vitality::{PlayerDead, PlayerVitality};
 
-use super::{Player, PlayerBundle};
+use super::{
+    input::PlayerInputBuffer,
+    spawn::SpawnPoints,
+    validation::{PlayerUpdateValidation, PlayerUpdateValidator, ValidUpdate},
+    Player, PlayerBundle,
+};
 use crate::{networking::prelude::*, state::ReceiveGameUpdates};
 
 pub fn build(app: &mut App) {
-    app.add_plugins(MessageQueuePlugin::<PlayerUpdateQueue>::default());
+    app.add_plugins((
+        MessageQueuePlugin::<PlayerUpdateQueue>::default(),
+        DespawnReplicationPlugin::<PlayerUpdateQueue>::default(),
+    ));
+
+    let orphaned_player_policy = app
+        .world()
+        .resource::<crate::ServerConfig>()
+        .orphaned_player_policy();
+    app.insert_resource(orphaned_player_policy);
+
+    app.add_systems(
+        PreUpdate,
+        orphan_disconnected_players.after(crate::networking::InitializeClients),
+    );
 
     app.add_systems(
         Update,
@@ -17,12 +44,13 @@ pub fn build(app: &mut App) {
             broadcast_new_players,
             init_existing_players,
             receive_state_updates,
+            despawn_orphaned_players.after(process_join_requests),
         ),
     );
 }
 
 /// Marker type for the player update queue
-struct PlayerUpdateQueue;
+pub struct PlayerUpdateQueue;
 
 /// exists on a [Player] entity and points to it's client entity
 ///
@@ -43,6 +71,69 @@ pub struct ConnectedPlayer {
     player_entity: Entity,
 }
 
+/// what happens to a [Player] when it's client disconnects
+///
+/// set by the `orphaned_player_grace_period_secs` config option
+#[derive(Resource, Clone, Copy, Debug)]
+pub enum OrphanedPlayerPolicy {
+    /// the player is despawned as soon as the client disconnects
+    Despawn,
+    /// the player stays in the world for this long before being despawned
+    GracePeriod(Duration),
+}
+
+/// exists on a [Player] entity that has lost it's client
+///
+/// stores the elapsed time when the client disconnected
+#[derive(Component)]
+pub struct OrphanedPlayer {
+    since: Duration,
+}
+
+/// the session of a [Player]
+///
+/// a client that joins with this token will reclaim the player
+#[derive(Component)]
+pub struct PlayerSession {
+    resume_token: ResumeToken,
+}
+
+impl PlayerSession {
+    fn new() -> Self {
+        PlayerSession {
+            resume_token: ResumeToken(rand::random()),
+        }
+    }
+
+    pub fn resume_token(&self) -> ResumeToken {
+        self.resume_token
+    }
+}
+
+/// Spawns a player without a client, such as one restored from a save.
+///
+/// A client can take over the player with the resume token before the [OrphanedPlayerPolicy] despawns it.
+pub fn spawn_orphaned_player(
+    commands: &mut Commands,
+    username: String,
+    resume_token: ResumeToken,
+    position: Vec3,
+    vitality: PlayerVitality,
+) -> Entity {
+    commands
+        .spawn((
+            PlayerBundle {
+                vitality,
+                ..PlayerBundle::new(username, position)
+            },
+            PlayerSession { resume_token },
+            OrphanedPlayer {
+                since: Duration::ZERO,
+            },
+        ))
+        .id()
+}
+
 impl ConnectedClient {
     pub fn get(&self) -> Entity {
         self.client_entity
@@ -55,6 +146,9 @@ impl ConnectedPlayer {
     }
 }
 
+/// replies to every join request with [JoinAccepted] or [JoinRejected]
+///
+/// join requests are left unread until the client's protocol has been verified
 fn process_join_requests(
     mut commands: Commands,
     mut client_q: Query<
@@ -63,13 +157,33 @@ fn process_join_requests(
             &mut ReceivedMessages<JoinRequest>,
             Has<ConnectedPlayer>,
         ),
-        With<ClientConnection>,
+        (With<ClientConnection>, With<ProtocolVerified>),
     >,
+    player_q: Query<(
+        Entity,
+        &Player,
+        &PlayerSession,
+        &Position,
+        Option<&ConnectedClient>,
+    )>,
+    mut messages: QueuedMessageSender<PlayerUpdateQueue>,
+    new_player_message_id: Res<MessageId<NewPlayer>>,
+    accepted_message_id: Res<MessageId<JoinAccepted>>,
+    rejected_message_id: Res<MessageId<JoinRejected>>,
+    server_config: Res<crate::ServerConfig>,
+    spawn_points: SpawnPoints,
 ) {
     let mut connected_this_tick = Vec::new();
+    let mut resumed_this_tick = Vec::new();
+    let mut usernames_this_tick = Vec::new();
 
-    for (client_entity, mut messages, has_connected_player) in client_q.iter_mut() {
-        for JoinRequest { username } in messages.drain() {
+    for (client_entity, mut join_messages, has_connected_player) in client_q.iter_mut() {
+        for JoinRequest {
+            username,
+            password,
+            resume_token,
+        } in join_messages.drain()
+        {
             info!(
                 "client {} wants to join with username \"{}\"",
                 client_entity, username
@@ -80,22 +194,139 @@ fn process_join_requests(
                 continue;
             }
 
-            connected_this_tick.push(client_entity);
+            let mut reject = |reason| {
+                info!("rejected client {}: {}", client_entity, reason);
 
-            let player_entity = commands
-                .spawn((
-                    PlayerBundle::new(username),
-                    ConnectedClient { client_entity },
-                ))
-                .id();
+                messages.send(*rejected_message_id, client_entity, JoinRejected { reason });
+            };
+
+            if server_config.password.is_some() && password != server_config.password {
+                reject(JoinRejectReason::BadPassword);
+                continue;
+            }
+
+            let resumed_player = resume_token.and_then(|resume_token| {
+                player_q
+                    .iter()
+                    .find(|(player_entity, _, session, ..)| {
+                        session.resume_token == resume_token
+                            && !resumed_this_tick.contains(player_entity)
+                    })
+                    .or_else(|| {
+                        info!(
+                            "client {} tried to resume a session that doesn't exist",
+                            client_entity
+                        );
+                        None
+                    })
+            });
+
+            let (player_entity, resume_token) =
+                if let Some((player_entity, player, session, &Position(position), old_client)) =
+                    resumed_player
+                {
+                    resumed_this_tick.push(player_entity);
+
+                    // take the player from the old client if it hasn't disconnected yet
+                    if let Some(old_client) = old_client {
+                        commands
+                            .entity(old_client.get())
+                            .remove::<(ConnectedPlayer, ReceiveGameUpdates)>();
+
+                        warn!(
+                            "client {} took player {} from client {}",
+                            client_entity,
+                            player_entity,
+                            old_client.get()
+                        );
+                    }
+
+                    commands
+                        .entity(player_entity)
+                        .remove::<OrphanedPlayer>()
+                        .insert((
+                            ConnectedClient { client_entity },
+                            PlayerInputBuffer::default(),
+                            // the new client's clock is different
+                            PlayerUpdateValidation::default(),
+                        ));
+
+                    // other clients already know about this player,
+                    // only the resuming client needs to initialize it
+                    messages.send(
+                        *new_player_message_id,
+                        client_entity,
+                        NewPlayer {
+                            server_entity: player_entity.into(),
+                            username: player.username.clone(),
+                            local_player: Some(NewLocalPlayer { position }),
+                        },
+                    );
+
+                    info!(
+                        "client {} resumed the session of player {}",
+                        client_entity, player_entity
+                    );
+
+                    (player_entity, session.resume_token)
+                } else {
+                    if !valid_username(&username) {
+                        reject(JoinRejectReason::InvalidUsername);
+                        continue;
+                    }
+
+                    let username_taken = usernames_this_tick.contains(&username)
+                        || player_q
+                            .iter()
+                            .any(|(_, player, ..)| player.username == username);
+
+                    if username_taken {
+                        reject(JoinRejectReason::DuplicateUsername);
+                        continue;
+                    }
+
+                    if player_q.iter().count() + usernames_this_tick.len()
+                        >= server_config.max_players
+                    {
+                        reject(JoinRejectReason::ServerFull);
+                        continue;
+                    }
+
+                    usernames_this_tick.push(username.clone());
+
+                    let session = PlayerSession::new();
+                    let resume_token = session.resume_token;
+
+                    let player_entity = commands
+                        .spawn((
+                            PlayerBundle::new(username, spawn_points.choose()),
+                            ConnectedClient { client_entity },
+                            session,
+                        ))
+                        .id();
+
+                    info!(
+                        "client {} joined as player {}",
+                        client_entity, player_entity
+                    );
+
+                    (player_entity, resume_token)
+                };
+
+            connected_this_tick.push(client_entity);
 
             commands
                 .entity(client_entity)
                 .insert((ConnectedPlayer { player_entity }, ReceiveGameUpdates));
 
-            info!(
-                "client {} joined as player {}",
-                client_entity, player_entity
+            messages.send(
+                *accepted_message_id,
+                client_entity,
+                JoinAccepted {
+                    resume_token,
+                    movement_mode: server_config.movement_mode,
+                    tick_interval: server_config.tick_interval(),
+                },
             );
         }
     }
@@ -154,21 +385,42 @@ fn init_existing_players(
     }
 }
 
+/// Components of a player used to apply a [ClientPlayerUpdate].
+type PlayerUpdateData<'a> = (
+    &'a mut Position,
+    &'a mut LinearVelocity,
+    &'a mut PlayerInput,
+    &'a mut PlayerUpdateValidation,
+    Has<PlayerDead>,
+);
+
+/// Applies player updates from clients that are possible with the player controller,
+/// otherwise sends a [PlayerCorrection] to move the client back to the server's state.
+///
+/// Updates are ignored if the [MovementMode] is server authoritative.
 fn receive_state_updates(
     mut client_q: Query<(
         Entity,
         &mut ReceivedMessages<ClientPlayerUpdate>,
         Option<&ConnectedPlayer>,
     )>,
-    mut player_q: Query<(&mut Position, &mut LinearVelocity, &mut PlayerInput)>,
+    mut player_q: Query<PlayerUpdateData>,
+    validator: PlayerUpdateValidator,
+    time: Res<Time>,
+    mut messages: QueuedMessageSender<PlayerUpdateQueue>,
+    message_id: Res<MessageId<PlayerCorrection>>,
+    server_config: Res<crate::ServerConfig>,
 ) {
-    for (client_entity, mut messages, connected_player) in client_q.iter_mut() {
-        for ClientPlayerUpdate {
-            position,
-            linear_velocity,
-            input,
-        } in messages.drain()
-        {
+    for (client_entity, mut client_messages, connected_player) in client_q.iter_mut() {
+        for update in client_messages.drain() {
+            if server_config.movement_mode == MovementMode::ServerAuthoritative {
+                warn!(
+                    "client {} sent a player state update but the server is authoritative",
+                    client_entity
+                );
+                continue;
+            }
+
             let Some(connected_player) = connected_player else {
                 warn!(
                     "client {} sent a player state update but they don't have a connected player",
@@ -179,8 +431,13 @@ fn receive_state_updates(
 
             let player_entity = connected_player.get();
 
-            let Ok((mut player_position, mut player_velocity, mut player_input)) =
-                player_q.get_mut(player_entity)
+            let Ok((
+                mut player_position,
+                mut player_velocity,
+                mut player_input,
+                mut validation,
+                dead,
+            )) = player_q.get_mut(player_entity)
             else {
                 error!(
                     "couldn't query client {}'s connected player {} to apply a state update",
@@ -189,9 +446,135 @@ fn receive_state_updates(
                 continue;
             };
 
-            player_position.0 = position;
-            player_velocity.0 = linear_velocity;
-            *player_input = input;
+            // dead players can't move
+            if dead {
+                continue;
+            }
+
+            let now = time.elapsed();
+
+            if now < validation.ignore_until {
+                continue;
+            }
+
+            // updates are sent unordered, late ones are dropped
+            if validation
+                .last_valid
+                .is_some_and(|last_valid| update.time <= last_valid.time)
+            {
+                continue;
+            }
+
+            let last_valid = validation.last_valid.unwrap_or_else(|| {
+                ValidUpdate::from_server(player_position.0, player_velocity.0, update.time)
+            });
+
+            let result = validation
+                .check_clock(update.time, now)
+                .and_then(|()| validator.validate(&update, &last_valid, player_entity));
+
+            let valid = match result {
+                Ok(valid) => valid,
+                Err(violation) => {
+                    warn!(
+                        "rejected player {}'s update from client {}: {:?}",
+                        player_entity, client_entity, violation
+                    );
+
+                    validation.reset(now);
+
+                    messages.send(
+                        *message_id,
+                        client_entity,
+                        PlayerCorrection {
+                            position: player_position.0,
+                            linear_velocity: player_velocity.0,
+                        },
+                    );
+
+                    continue;
+                }
+            };
+
+            validation.last_valid = Some(valid);
+
+            player_position.0 = update.position;
+            player_velocity.0 = update.linear_velocity;
+            // the client already jumped, the server's player controller mustn't jump again
+            *player_input = PlayerInput {
+                jump: false,
+                ..update.input
+            };
+        }
+    }
+}
+
+/// removes the connection between a disconnected client and it's [Player]
+/// and marks the player as an [OrphanedPlayer]
+fn orphan_disconnected_players(
+    mut commands: Commands,
+    mut disconnected_r: EventReader<ClientDisconnected>,
+    player_q: Query<(Entity, &ConnectedClient)>,
+    time: Res<Time>,
+) {
+    for &ClientDisconnected { client_entity } in disconnected_r.read() {
+        if let Some(mut client_commands) = commands.get_entity(client_entity) {
+            client_commands.remove::<(ConnectedPlayer, ReceiveGameUpdates)>();
+        }
+
+        for (player_entity, connected_client) in player_q.iter() {
+            if connected_client.get() != client_entity {
+                continue;
+            }
+
+            commands
+                .entity(player_entity)
+                .remove::<ConnectedClient>()
+                .insert(OrphanedPlayer {
+                    since: time.elapsed(),
+                });
+
+            info!(
+                "player {} lost it's client {}",
+                player_entity, client_entity
+            );
+        }
+    }
+}
+
+/// despawns [OrphanedPlayer]s according to the [OrphanedPlayerPolicy]
+/// and informs clients that they have left
+fn despawn_orphaned_players(
+    mut commands: Commands,
+    player_q: Query<(Entity, &Player, &OrphanedPlayer)>,
+    client_q: Query<Entity, With<ReceiveGameUpdates>>,
+    policy: Res<OrphanedPlayerPolicy>,
+    time: Res<Time>,
+    mut messages: QueuedMessageSender<PlayerUpdateQueue>,
+    message_id: Res<MessageId<PlayerLeft>>,
+) {
+    for (player_entity, player, orphaned) in player_q.iter() {
+        if let OrphanedPlayerPolicy::GracePeriod(grace_period) = *policy {
+            if time.elapsed() < orphaned.since + grace_period {
+                continue;
+            }
+        }
+
+        commands.entity(player_entity).despawn_recursive();
+
+        info!(
+            "despawned orphaned player {} \"{}\"",
+            player_entity, player.username
+        );
+
+        for client_entity in client_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                PlayerLeft {
+                    server_entity: player_entity.into(),
+                },
+            );
         }
     }
 }
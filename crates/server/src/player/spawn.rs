@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use common::player::{
+    controller::PlayerInput,
+    vitality::{PlayerDead, PlayerVitality},
+    PlayerRespawned, RESET_FLOOR,
+};
+use rand::seq::IteratorRandom;
+
+use crate::{networking::prelude::*, state::ReceiveGameUpdates};
+
+use super::{networking::PlayerUpdateQueue, validation::PlayerUpdateValidation, Player};
+
+/// Where players spawn if there are no spawn points.
+const DEFAULT_SPAWN_POSITION: Vec3 = Vec3::new(0., 1., 0.);
+
+pub fn build(app: &mut App) {
+    app.add_event::<RespawnPlayer>();
+
+    app.add_systems(
+        FixedUpdate,
+        (
+            schedule_death_respawns,
+            respawn_dead_players,
+            respawn_fallen_players,
+            respawn_players,
+        )
+            .chain(),
+    );
+}
+
+/// A point that players spawn and respawn at, usually an element of a module.
+#[derive(Component)]
+#[require(Transform)]
+pub struct SpawnPoint;
+
+/// Used to choose where players spawn.
+#[derive(SystemParam)]
+pub struct SpawnPoints<'w, 's> {
+    spawn_point_q: Query<'w, 's, &'static GlobalTransform, With<SpawnPoint>>,
+}
+
+impl SpawnPoints<'_, '_> {
+    /// Chooses a random spawn point, or a default position if there are none.
+    pub fn choose(&self) -> Vec3 {
+        self.spawn_point_q
+            .iter()
+            .choose(&mut rand::thread_rng())
+            .map(|transform| transform.translation())
+            .unwrap_or(DEFAULT_SPAWN_POSITION)
+    }
+}
+
+/// Fire this event to move a player to a spawn point and reset their vitality.
+#[derive(Event)]
+pub struct RespawnPlayer {
+    pub player_entity: Entity,
+}
+
+/// Inserted on dead players until they are respawned.
+#[derive(Component)]
+struct RespawnTimer {
+    at: Duration,
+}
+
+fn schedule_death_respawns(
+    mut commands: Commands,
+    player_q: Query<Entity, Added<PlayerDead>>,
+    server_config: Res<crate::ServerConfig>,
+    time: Res<Time>,
+) {
+    for player_entity in player_q.iter() {
+        commands.entity(player_entity).insert(RespawnTimer {
+            at: time.elapsed() + server_config.respawn_delay(),
+        });
+    }
+}
+
+fn respawn_dead_players(
+    player_q: Query<(Entity, &RespawnTimer)>,
+    mut respawn_w: EventWriter<RespawnPlayer>,
+    time: Res<Time>,
+) {
+    for (player_entity, timer) in player_q.iter() {
+        if time.elapsed() >= timer.at {
+            respawn_w.send(RespawnPlayer { player_entity });
+        }
+    }
+}
+
+fn respawn_fallen_players(
+    player_q: Query<(Entity, &Position), With<Player>>,
+    mut respawn_w: EventWriter<RespawnPlayer>,
+) {
+    for (player_entity, position) in player_q.iter() {
+        if position.y < RESET_FLOOR {
+            debug!("player {} fell out of the ship", player_entity);
+
+            respawn_w.send(RespawnPlayer { player_entity });
+        }
+    }
+}
+
+fn respawn_players(
+    mut commands: Commands,
+    mut respawn_r: EventReader<RespawnPlayer>,
+    mut player_q: Query<
+        (
+            &mut Position,
+            &mut LinearVelocity,
+            &mut PlayerInput,
+            &mut PlayerVitality,
+            &mut PlayerUpdateValidation,
+        ),
+        With<Player>,
+    >,
+    spawn_points: SpawnPoints,
+    client_q: Query<Entity, With<ReceiveGameUpdates>>,
+    mut messages: QueuedMessageSender<PlayerUpdateQueue>,
+    message_id: Res<MessageId<PlayerRespawned>>,
+    time: Res<Time>,
+) {
+    for &RespawnPlayer { player_entity } in respawn_r.read() {
+        let Ok((mut position, mut velocity, mut input, mut vitality, mut validation)) =
+            player_q.get_mut(player_entity)
+        else {
+            error!("couldn't query player {} to respawn", player_entity);
+            continue;
+        };
+
+        position.0 = spawn_points.choose();
+        velocity.0 = Vec3::ZERO;
+        input.target_velocity = Vec2::ZERO;
+        *vitality = PlayerVitality::default();
+        validation.reset(time.elapsed());
+
+        commands
+            .entity(player_entity)
+            .remove::<(PlayerDead, RespawnTimer)>();
+
+        info!("player {} respawned at {}", player_entity, position.0);
+
+        for client_entity in client_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                PlayerRespawned {
+                    server_entity: player_entity.into(),
+                    position: position.0,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use common::{
+    player::{
+        controller::{player_on_ground, PLAYER_ACCELERATION, PLAYER_JUMP_SPEED, PLAYER_MOVE_SPEED},
+        player_collider, ClientPlayerUpdate,
+    },
+    GameLayer,
+};
+
+/// How much faster than the player controller allows a client can move,
+/// to allow for jitter in when updates arrive.
+const SPEED_TOLERANCE: f32 = 1.5;
+/// Distance in meters that a client's position can be off by.
+const POSITION_TOLERANCE: f32 = 0.5;
+/// Vertical speed in meters per second that a client in the air can be off by.
+const FALL_SPEED_TOLERANCE: f32 = 0.5;
+/// How far a client's clock can get ahead of the server's, to allow for jitter in when updates arrive.
+const MAX_CLOCK_DRIFT: Duration = Duration::from_millis(500);
+/// Time allowed for the first update after a player spawns or is corrected.
+const FIRST_UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+/// How long updates are ignored after the server moves a player,
+/// because updates sent before the client found out would be rejected.
+pub const CORRECTION_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// State used to validate a player's [ClientPlayerUpdate]s.
+#[derive(Component, Default)]
+pub struct PlayerUpdateValidation {
+    /// the last update the server accepted,
+    /// `None` after the server moves the player so the next update is checked against the server's state
+    pub last_valid: Option<ValidUpdate>,
+    /// updates received before this are ignored
+    pub ignore_until: Duration,
+    /// the client's time and the server's time when the first update since the last reset was received
+    clock_start: Option<(Duration, Duration)>,
+}
+
+impl PlayerUpdateValidation {
+    /// Call when the server moves the player itself.
+    pub fn reset(&mut self, now: Duration) {
+        self.last_valid = None;
+        self.ignore_until = now + CORRECTION_GRACE_PERIOD;
+        self.clock_start = None;
+    }
+
+    /// Checks that the client's clock isn't running faster than the server's,
+    /// because the time between updates is measured with the client's clock.
+    pub fn check_clock(
+        &mut self,
+        client_time: Duration,
+        now: Duration,
+    ) -> Result<(), MovementViolation> {
+        let &mut (client_start, server_start) = self.clock_start.get_or_insert((client_time, now));
+
+        if client_time.saturating_sub(client_start) > now - server_start + MAX_CLOCK_DRIFT {
+            return Err(MovementViolation::ClockAhead);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ValidUpdate {
+    pub position: Vec3,
+    pub linear_velocity: Vec3,
+    /// the client's time when it sent the update
+    pub time: Duration,
+    pub on_ground: bool,
+    /// height of the last position the player was on the ground at
+    pub ground_height: f32,
+}
+
+/// Reason a [ClientPlayerUpdate] was rejected.
+#[derive(Debug)]
+pub enum MovementViolation {
+    /// the input asked for more than the maximum speed
+    InvalidInput,
+    /// moving horizontally faster than the player controller allows
+    TooFast,
+    /// speeding up faster than the player controller allows
+    TooMuchAcceleration,
+    /// moving upwards faster than a jump, higher than a jump can reach or not falling while in the air
+    Flying,
+    /// moving further than possible since the last update
+    Teleported,
+    /// moving through world geometry
+    ThroughWall,
+    /// the client's clock is running faster than the server's
+    ClockAhead,
+}
+
+/// Checks [ClientPlayerUpdate]s against what the player controller allows.
+#[derive(SystemParam)]
+pub struct PlayerUpdateValidator<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    gravity: Res<'w, Gravity>,
+}
+
+impl PlayerUpdateValidator<'_, '_> {
+    /// Checks a client's update against what the player controller allows since the last valid update,
+    /// returning the update as the new last valid update.
+    pub fn validate(
+        &self,
+        update: &ClientPlayerUpdate,
+        last: &ValidUpdate,
+        player_entity: Entity,
+    ) -> Result<ValidUpdate, MovementViolation> {
+        let elapsed = update.time.saturating_sub(last.time).as_secs_f32();
+
+        if update.input.target_velocity.length() > PLAYER_MOVE_SPEED + f32::EPSILON * 10. {
+            return Err(MovementViolation::InvalidInput);
+        }
+
+        let speed = update.linear_velocity.xz().length();
+        let last_speed = last.linear_velocity.xz().length();
+
+        if speed > PLAYER_MOVE_SPEED * SPEED_TOLERANCE {
+            return Err(MovementViolation::TooFast);
+        }
+
+        // collisions can slow the player down suddenly so only speeding up is checked
+        if speed - last_speed > PLAYER_ACCELERATION * elapsed * SPEED_TOLERANCE {
+            return Err(MovementViolation::TooMuchAcceleration);
+        }
+
+        let gravity = -self.gravity.0.y;
+        let on_ground = player_on_ground(&self.spatial_query, update.position);
+
+        // gravity means a player can only get higher than the ground it last stood on by jumping
+        let max_jump_height = PLAYER_JUMP_SPEED * PLAYER_JUMP_SPEED / (2. * gravity);
+
+        if update.linear_velocity.y > PLAYER_JUMP_SPEED * SPEED_TOLERANCE
+            || update.position.y - last.ground_height
+                > max_jump_height * SPEED_TOLERANCE + POSITION_TOLERANCE
+        {
+            return Err(MovementViolation::Flying);
+        }
+
+        // and while in the air it can only fall
+        if !last.on_ground
+            && !on_ground
+            && update.linear_velocity.y
+                > last.linear_velocity.y - gravity * elapsed + FALL_SPEED_TOLERANCE
+        {
+            return Err(MovementViolation::Flying);
+        }
+
+        let displacement = update.position - last.position;
+        let max_distance = PLAYER_MOVE_SPEED * SPEED_TOLERANCE * elapsed + POSITION_TOLERANCE;
+
+        if displacement.xz().length() > max_distance {
+            return Err(MovementViolation::Teleported);
+        }
+
+        let valid = ValidUpdate {
+            position: update.position,
+            linear_velocity: update.linear_velocity,
+            time: update.time,
+            on_ground,
+            ground_height: if on_ground {
+                update.position.y
+            } else {
+                last.ground_height
+            },
+        };
+
+        // the player can't have passed through anything in the world to get to the new position
+        let Ok(direction) = Dir3::new(displacement) else {
+            return Ok(valid);
+        };
+
+        let distance = displacement.length();
+
+        let mut shape = player_collider();
+        shape.set_scale(Vec3::splat(0.9), 10);
+
+        let blocked = self
+            .spatial_query
+            .shape_hits(
+                &shape,
+                last.position,
+                Quat::IDENTITY,
+                direction,
+                u32::MAX,
+                &ShapeCastConfig {
+                    max_distance: distance,
+                    ignore_origin_penetration: true,
+                    ..default()
+                },
+                &SpatialQueryFilter::from_mask([GameLayer::World])
+                    .with_excluded_entities(std::iter::once(player_entity)),
+            )
+            .into_iter()
+            // sliding along surfaces is fine, only moving into them is checked
+            .filter(|hit| -hit.normal1.dot(direction.into()) > 0.5)
+            .any(|hit| hit.distance < distance - POSITION_TOLERANCE);
+
+        if blocked {
+            return Err(MovementViolation::ThroughWall);
+        }
+
+        Ok(valid)
+    }
+}
+
+impl ValidUpdate {
+    /// The state to check the first update against when there is no previous valid update.
+    ///
+    /// `client_time` is the time of the update being checked.
+    pub fn from_server(position: Vec3, linear_velocity: Vec3, client_time: Duration) -> Self {
+        ValidUpdate {
+            position,
+            linear_velocity,
+            time: client_time.saturating_sub(FIRST_UPDATE_INTERVAL),
+            // not known, assume the player was on the ground
+            on_ground: true,
+            ground_height: position.y,
+        }
+    }
+}
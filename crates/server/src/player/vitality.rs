@@ -1,24 +1,25 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use common::player::vitality::*;
+use common::{
+    physics::SimulationTick,
+    player::{controller::PlayerInput, vitality::*},
+};
 
 use crate::{
     modules::{atmosphere::ModuleAtmosphere, grid::ShipGridPresence},
     networking::prelude::*,
+    state::ReceiveGameUpdates,
 };
 
-use super::networking::ConnectedClient;
-
-/// How much of a player's oxygen is refilled per unit of module atmosphere.
-const PLAYER_OXYGEN_REFILL_RATIO: f32 = 50.;
-/// How much of a player's oxygen is refilled per second.
-const PLAYER_OXYGEN_REFILL_RATE: f32 = 10.;
+use super::networking::{ConnectedClient, PlayerUpdateQueue};
 
 const VITALITY_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
 
 pub fn build(app: &mut App) {
-    app.add_systems(Update, (send_vitality_updates, update_oxygen));
+    app.add_systems(FixedUpdate, (update_oxygen, damage_players).chain());
+    app.add_systems(FixedLast, send_vitality_updates);
+    app.add_systems(PostUpdate, init_dead_players);
 }
 
 fn send_vitality_updates(
@@ -26,6 +27,7 @@ fn send_vitality_updates(
     mut messages: MessageSender,
     message_id: Res<MessageId<UpdatePlayerVitality>>,
     time: Res<Time>,
+    tick: Res<SimulationTick>,
     mut last_update: Local<Duration>,
 ) {
     if time.elapsed() - *last_update > VITALITY_UPDATE_INTERVAL {
@@ -37,6 +39,7 @@ fn send_vitality_updates(
                 client.get(),
                 &UpdatePlayerVitality {
                     vitality: *vitality,
+                    tick: *tick,
                 },
             );
         }
@@ -44,13 +47,14 @@ fn send_vitality_updates(
 }
 
 fn update_oxygen(
-    mut player_q: Query<(&mut PlayerVitality, &ShipGridPresence)>,
+    mut player_q: Query<(&mut PlayerVitality, &ShipGridPresence), Without<PlayerDead>>,
     mut module_q: Query<&mut ModuleAtmosphere>,
     time: Res<Time>,
+    server_config: Res<crate::ServerConfig>,
 ) {
     for (mut vitality, grid_presence) in player_q.iter_mut() {
         // Remove oxygen from the player
-        vitality.oxygen = vitality.oxygen - time.delta_secs();
+        vitality.oxygen = (vitality.oxygen - time.delta_secs()).max(0.);
 
         // If the player is in a module refill their tank from that module
         if let Some(module_entity) = grid_presence.current_module {
@@ -63,17 +67,97 @@ fn update_oxygen(
 
             // Find the required amount of oxygen to refill this tick.
             let difference = MAX_OXYGEN - vitality.oxygen;
-            let required_tank = difference.min(time.delta_secs() * PLAYER_OXYGEN_REFILL_RATE);
-            let required_module = required_tank / PLAYER_OXYGEN_REFILL_RATIO;
+            let required_tank =
+                difference.min(time.delta_secs() * server_config.player_oxygen_refill_rate);
+            let required_module = required_tank / server_config.player_oxygen_refill_ratio;
 
             // Find how much oxygen the player can refill from the module.
             let satisfaction = 1.0f32.min(module_atmosphere.level / required_module);
 
             // Update the player's oxygen level and the module's oxygen level.
-            vitality.oxygen += required_tank * satisfaction;
+            vitality.oxygen = (vitality.oxygen + required_tank * satisfaction).min(MAX_OXYGEN);
             module_atmosphere.level -= required_module * satisfaction;
         } else {
             vitality.pressure = 0.0;
         }
     }
 }
+
+/// Damages players that are out of oxygen or in low pressure and kills them when their health runs out.
+fn damage_players(
+    mut commands: Commands,
+    mut player_q: Query<(Entity, &mut PlayerVitality, &mut PlayerInput), Without<PlayerDead>>,
+    client_q: Query<Entity, With<ReceiveGameUpdates>>,
+    mut messages: QueuedMessageSender<PlayerUpdateQueue>,
+    message_id: Res<MessageId<PlayerDied>>,
+    time: Res<Time>,
+    server_config: Res<crate::ServerConfig>,
+) {
+    for (player_entity, mut vitality, mut input) in player_q.iter_mut() {
+        let suffocating = vitality.oxygen <= 0.;
+
+        let mut damage = 0.;
+
+        if suffocating {
+            damage += server_config.suffocation_damage_rate;
+        }
+
+        if vitality.pressure < server_config.low_pressure_threshold {
+            let missing_pressure = 1. - vitality.pressure / server_config.low_pressure_threshold;
+            damage += server_config.low_pressure_damage_rate * missing_pressure;
+        }
+
+        vitality.health = (vitality.health - damage * time.delta_secs()).max(0.);
+
+        if vitality.health > 0. {
+            continue;
+        }
+
+        let cause = if suffocating {
+            DeathCause::Suffocation
+        } else {
+            DeathCause::Decompression
+        };
+
+        commands.entity(player_entity).insert(PlayerDead { cause });
+
+        // updates from the client are ignored once dead, so stop the player here
+        input.target_velocity = Vec2::ZERO;
+
+        info!("player {} died: {}", player_entity, cause);
+
+        for client_entity in client_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                PlayerDied {
+                    server_entity: player_entity.into(),
+                    cause,
+                },
+            );
+        }
+    }
+}
+
+/// Tells new clients about players that are already dead.
+///
+/// Runs after players are initialized in [Update] so that the players exist on the client.
+fn init_dead_players(
+    client_q: Query<Entity, Added<ReceiveGameUpdates>>,
+    player_q: Query<(Entity, &PlayerDead)>,
+    mut messages: QueuedMessageSender<PlayerUpdateQueue>,
+    message_id: Res<MessageId<PlayerDied>>,
+) {
+    for client_entity in client_q.iter() {
+        for (player_entity, &PlayerDead { cause }) in player_q.iter() {
+            messages.send(
+                *message_id,
+                client_entity,
+                PlayerDied {
+                    server_entity: player_entity.into(),
+                    cause,
+                },
+            );
+        }
+    }
+}
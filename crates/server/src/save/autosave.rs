@@ -0,0 +1,187 @@
+use std::{
+    io::{BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use bevy::{app::AppExit, prelude::*};
+
+use super::{write_ship_save, ShipSaveQuery};
+
+pub fn build(app: &mut App) {
+    app.add_systems(PreStartup, check_unclean_shutdown.before(super::LoadShip));
+    app.add_systems(Update, autosave);
+    app.add_systems(Last, remove_lock_file_on_exit.after(super::save_ship));
+}
+
+/// Inserted at startup when recovering from an unclean shutdown,
+/// the ship is loaded from this autosave instead of the ship save.
+#[derive(Resource)]
+pub struct ResumeFromAutosave(pub PathBuf);
+
+/// The lock file exists while the server is running,
+/// if it exists at startup the server didn't shut down cleanly.
+fn lock_file_path(server_config: &crate::ServerConfig) -> PathBuf {
+    server_config.save_path.with_extension("lock")
+}
+
+/// Lists autosaves from oldest to newest.
+fn list_autosaves(autosave_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(autosave_dir) else {
+        return Vec::new();
+    };
+
+    let mut autosaves: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("autosave-") && name.ends_with(".ron"))
+        })
+        .collect();
+
+    // names contain a zero padded timestamp so they sort by age
+    autosaves.sort();
+
+    autosaves
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Decides whether to resume from an autosave.
+///
+/// Uses the `resume_autosave` config if set, otherwise asks on the terminal if there is one.
+fn should_resume(server_config: &crate::ServerConfig, autosave: &Path) -> bool {
+    if server_config.resume_autosave {
+        return true;
+    }
+
+    if !std::io::stdin().is_terminal() {
+        warn!(
+            "not resuming from autosave {:?}, set resume_autosave to resume from it",
+            autosave
+        );
+        return false;
+    }
+
+    eprint!("Resume from autosave {:?}? [y/N] ", autosave);
+    let _ = std::io::stderr().flush();
+
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+/// Checks for the lock file left by an unclean shutdown and offers to resume from the newest autosave,
+/// then creates the lock file for this session.
+fn check_unclean_shutdown(mut commands: Commands, server_config: Res<crate::ServerConfig>) {
+    let lock_path = lock_file_path(&server_config);
+
+    if lock_path.exists() {
+        warn!("the server didn't shut down cleanly last session");
+
+        let newest_autosave =
+            list_autosaves(&server_config.autosave_dir)
+                .pop()
+                .filter(|autosave| {
+                    // only offer the autosave if it is newer than the ship save
+                    match (
+                        modified_time(autosave),
+                        modified_time(&server_config.save_path),
+                    ) {
+                        (Some(autosave_time), Some(save_time)) => autosave_time > save_time,
+                        _ => true,
+                    }
+                });
+
+        match newest_autosave {
+            Some(autosave) => {
+                if should_resume(&server_config, &autosave) {
+                    info!("resuming from autosave {:?}", autosave);
+                    commands.insert_resource(ResumeFromAutosave(autosave));
+                } else {
+                    info!("loading the ship save instead of an autosave");
+                }
+            }
+            None => info!("no autosave newer than the ship save to resume from"),
+        }
+    }
+
+    if let Err(err) = std::fs::write(&lock_path, std::process::id().to_string()) {
+        error!("couldn't create lock file {:?}: {}", lock_path, err);
+    }
+}
+
+fn autosave(
+    server_config: Res<crate::ServerConfig>,
+    save_q: ShipSaveQuery,
+    time: Res<Time<Real>>,
+    mut last_autosave: Local<Duration>,
+) {
+    let Some(interval) = server_config.autosave_interval() else {
+        return;
+    };
+
+    if time.elapsed() - *last_autosave < interval {
+        return;
+    }
+
+    *last_autosave = time.elapsed();
+
+    let autosave_dir = &server_config.autosave_dir;
+
+    if let Err(err) = std::fs::create_dir_all(autosave_dir) {
+        error!(
+            "couldn't create autosave directory {:?}: {}",
+            autosave_dir, err
+        );
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = autosave_dir.join(format!("autosave-{:020}.ron", timestamp));
+
+    match write_ship_save(&path, &save_q.collect()) {
+        Ok(()) => debug!("autosaved to {:?}", path),
+        Err(err) => {
+            error!("couldn't autosave to {:?}: {}", path, err);
+            return;
+        }
+    }
+
+    // remove the oldest autosaves past the limit
+    let autosaves = list_autosaves(autosave_dir);
+    let excess = autosaves.len().saturating_sub(server_config.autosave_count);
+
+    for old_autosave in &autosaves[..excess] {
+        if let Err(err) = std::fs::remove_file(old_autosave) {
+            error!("couldn't remove old autosave {:?}: {}", old_autosave, err);
+        }
+    }
+}
+
+/// Removes the lock file once the ship has been saved on a clean shutdown.
+fn remove_lock_file_on_exit(
+    mut exit_r: EventReader<AppExit>,
+    server_config: Res<crate::ServerConfig>,
+) {
+    if exit_r.read().next().is_none() {
+        return;
+    }
+
+    let lock_path = lock_file_path(&server_config);
+
+    if let Err(err) = std::fs::remove_file(&lock_path) {
+        error!("couldn't remove lock file {:?}: {}", lock_path, err);
+    }
+}
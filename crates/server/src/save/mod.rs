@@ -0,0 +1,485 @@
+use std::path::{Path, PathBuf};
+
+use avian3d::prelude::*;
+use bevy::{app::AppExit, ecs::system::SystemParam, prelude::*};
+use common::{player::vitality::PlayerVitality, state::ResumeToken};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    elements::{door::Door, ship_map::ShipMap, tank::Tank},
+    modules::{
+        atmosphere::{ModuleAtmosphere, ModuleVent, TankAtmosphere},
+        grid::{ModuleRotation, ShipModuleGrid, ShipModuleTransform},
+        module_types::{spawn_ship_module, InitShipModules, ShipModule, ShipModuleTypes},
+    },
+    player::{
+        networking::{spawn_orphaned_player, PlayerSession},
+        Player,
+    },
+};
+
+pub mod autosave;
+
+/// Version of the ship save format.
+///
+/// Increase this when [ShipSave] changes and handle the old version in [read_ship_save].
+pub const SHIP_SAVE_VERSION: u32 = 3;
+
+pub fn build(app: &mut App) {
+    app.add_event::<SaveShip>();
+
+    app.add_systems(PreStartup, load_ship.in_set(LoadShip));
+    app.add_systems(Update, apply_saved_module_states.after(InitShipModules));
+    app.add_systems(Last, (save_ship_on_exit, save_ship).chain());
+
+    autosave::build(app);
+}
+
+/// System set in [PreStartup] where the ship is loaded from a save.
+#[derive(SystemSet, Hash, PartialEq, Eq, Debug, Clone)]
+pub struct LoadShip;
+
+/// Fire this event to write the ship to the save file.
+#[derive(Event)]
+pub struct SaveShip;
+
+/// Inserted at startup if the ship was loaded from a save,
+/// used to skip spawning the default ship.
+#[derive(Resource)]
+pub struct ShipLoadedFromSave;
+
+/// Inserted at startup if the ship save couldn't be loaded or moved aside,
+/// so that the ship spawned instead isn't written over it.
+///
+/// Autosaves are still written because they never replace the ship save,
+/// and they are the only copy of what was built this session.
+#[derive(Resource)]
+struct ShipSaveDisabled;
+
+/// The whole ship as it is written to disk.
+#[derive(Serialize, Deserialize)]
+pub struct ShipSave {
+    pub version: u32,
+    pub modules: Vec<ShipModuleSave>,
+    /// players are restored without a client and can be resumed until the orphaned player policy despawns them
+    ///
+    /// added in version 2
+    #[serde(default)]
+    pub players: Vec<PlayerSave>,
+}
+
+/// Used to read the version of a save before the rest of it.
+#[derive(Deserialize)]
+struct ShipSaveVersion {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ShipModuleSave {
+    /// name of the module type, used instead of the type id so that
+    /// saves aren't affected by the order module types are added
+    pub module_type: String,
+    pub transform: ShipModuleTransform,
+    pub state: SavedModuleState,
+}
+
+/// State of a module that is restored after the module has been initialized.
+///
+/// Elements are matched to the module's children in the order they were spawned.
+#[derive(Component, Serialize, Deserialize, Default)]
+pub struct SavedModuleState {
+    pub atmosphere: Option<SavedAtmosphere>,
+    pub vent_open: Option<bool>,
+    pub tanks: Vec<SavedTank>,
+    pub ship_maps: Vec<SavedShipMap>,
+    #[serde(default)]
+    pub doors: Vec<SavedDoor>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedAtmosphere {
+    pub level: f32,
+    pub breached: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTank {
+    pub level: f32,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedShipMap {
+    pub position: Vec2,
+    pub zoom: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedDoor {
+    pub open: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub username: String,
+    /// the player's [ResumeToken] as hex
+    pub resume_token: String,
+    pub position: Vec3,
+    pub vitality: PlayerVitality,
+}
+
+/// Reads a ship save, returning an error message if it is invalid.
+pub fn read_ship_save(path: &Path) -> Result<ShipSave, String> {
+    let file = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+    let ShipSaveVersion { version } = ron::from_str(&file).map_err(|err| err.to_string())?;
+
+    match version {
+        // version 1 saves don't have players, which default to empty
+        //
+        // before version 3 north and south rotated a module's grid spaces the opposite way to it's mesh,
+        // they are swapped so that modules keep the grid spaces they were saved in
+        1 | 2 => {
+            let mut save: ShipSave = ron::from_str(&file).map_err(|err| err.to_string())?;
+            save.version = SHIP_SAVE_VERSION;
+
+            for module in save.modules.iter_mut() {
+                module.transform.rotation = match module.transform.rotation {
+                    ModuleRotation::North => ModuleRotation::South,
+                    ModuleRotation::South => ModuleRotation::North,
+                    rotation => rotation,
+                };
+            }
+
+            Ok(save)
+        }
+        SHIP_SAVE_VERSION => ron::from_str(&file).map_err(|err| err.to_string()),
+        version => Err(format!(
+            "unsupported save version {}, expected {}",
+            version, SHIP_SAVE_VERSION
+        )),
+    }
+}
+
+/// Writes a ship save to a temporary file and then moves it over the old save,
+/// so that the old save is kept if writing fails.
+pub fn write_ship_save(path: &Path, save: &ShipSave) -> Result<(), String> {
+    let file = ron::ser::to_string_pretty(save, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())?;
+
+    let temp_path = path.with_extension("tmp");
+
+    std::fs::write(&temp_path, file).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+fn load_ship(
+    mut commands: Commands,
+    server_config: Res<crate::ServerConfig>,
+    modules: Res<ShipModuleTypes>,
+    mut grid: ResMut<ShipModuleGrid>,
+    resume_autosave: Option<Res<autosave::ResumeFromAutosave>>,
+) {
+    let path = match &resume_autosave {
+        Some(resume_autosave) => &resume_autosave.0,
+        None => &server_config.save_path,
+    };
+
+    if !path.exists() {
+        info!("no ship save at {:?}, spawning a new ship", path);
+        return;
+    }
+
+    let save = match read_ship_save(path) {
+        Ok(save) => save,
+        Err(err) => {
+            error!("couldn't load ship save {:?}: {}", path, err);
+            set_aside_ship_save(&mut commands, &server_config, path);
+            return;
+        }
+    };
+
+    // a save with unknown modules isn't loaded at all, saving it again would lose them
+    let mut module_type_ids = Vec::with_capacity(save.modules.len());
+    for module in &save.modules {
+        let Some(module_type_id) = modules.find(&module.module_type) else {
+            error!(
+                "unknown module type \"{}\" in ship save {:?}",
+                module.module_type, path
+            );
+            set_aside_ship_save(&mut commands, &server_config, path);
+            return;
+        };
+
+        module_type_ids.push(module_type_id);
+    }
+
+    for (
+        ShipModuleSave {
+            transform, state, ..
+        },
+        module_type_id,
+    ) in save.modules.into_iter().zip(module_type_ids)
+    {
+        let Some(module_entity) = spawn_ship_module(
+            &mut commands,
+            modules.as_ref(),
+            grid.as_mut(),
+            module_type_id,
+            transform,
+        ) else {
+            continue;
+        };
+
+        commands.entity(module_entity).insert(state);
+    }
+
+    for PlayerSave {
+        username,
+        resume_token,
+        position,
+        vitality,
+    } in save.players
+    {
+        let Ok(resume_token) = u128::from_str_radix(&resume_token, 16) else {
+            error!(
+                "invalid resume token for player \"{}\" in ship save",
+                username
+            );
+            continue;
+        };
+
+        let player_entity = spawn_orphaned_player(
+            &mut commands,
+            username,
+            ResumeToken(resume_token),
+            position,
+            vitality,
+        );
+
+        debug!("restored player {} from ship save", player_entity);
+    }
+
+    commands.insert_resource(ShipLoadedFromSave);
+
+    info!("loaded ship save {:?}", path);
+}
+
+/// Moves a save that couldn't be loaded to a `.corrupt` file so that the ship spawned instead doesn't replace it.
+///
+/// If it can't be moved saving is disabled for this session.
+fn set_aside_ship_save(commands: &mut Commands, server_config: &crate::ServerConfig, path: &Path) {
+    let corrupt_path = corrupt_save_path(path);
+
+    match std::fs::rename(path, &corrupt_path) {
+        Ok(()) => warn!("moved ship save {:?} to {:?}", path, corrupt_path),
+        Err(err) => {
+            error!(
+                "couldn't move ship save {:?} to {:?}: {}",
+                path, corrupt_path, err
+            );
+
+            warn!(
+                "saving to {:?} is disabled until the server is restarted",
+                server_config.save_path
+            );
+            commands.insert_resource(ShipSaveDisabled);
+        }
+    }
+}
+
+/// Finds a `.corrupt` path for a save that doesn't replace an earlier corrupt save.
+fn corrupt_save_path(path: &Path) -> PathBuf {
+    let mut corrupt_path = path.with_extension("corrupt");
+    let mut count = 1;
+
+    while corrupt_path.exists() {
+        corrupt_path = path.with_extension(format!("{}.corrupt", count));
+        count += 1;
+    }
+
+    corrupt_path
+}
+
+/// Restores saved state onto modules once they have been initialized.
+fn apply_saved_module_states(
+    mut commands: Commands,
+    mut module_q: Query<(
+        Entity,
+        &SavedModuleState,
+        Option<&mut ModuleAtmosphere>,
+        Option<&mut ModuleVent>,
+        Option<&Children>,
+    )>,
+    mut tank_q: Query<&mut TankAtmosphere, With<Tank>>,
+    mut map_q: Query<&mut ShipMap>,
+    mut door_q: Query<&mut Door>,
+) {
+    for (module_entity, state, atmosphere, vent, children) in module_q.iter_mut() {
+        commands.entity(module_entity).remove::<SavedModuleState>();
+
+        if let (Some(mut atmosphere), Some(saved)) = (atmosphere, &state.atmosphere) {
+            atmosphere.level = saved.level.clamp(0., atmosphere.volume);
+            atmosphere.breached = saved.breached;
+        }
+
+        if let (Some(mut vent), Some(open)) = (vent, state.vent_open) {
+            vent.open = open;
+        }
+
+        let children = children.map(|children| &children[..]).unwrap_or(&[]);
+
+        let mut tanks = tank_q.iter_many_mut(children);
+        let mut saved_tanks = state.tanks.iter();
+        while let (Some(mut tank), Some(saved)) = (tanks.fetch_next(), saved_tanks.next()) {
+            tank.level = saved.level.clamp(0., tank.volume);
+            tank.enabled = saved.enabled;
+        }
+
+        let mut maps = map_q.iter_many_mut(children);
+        let mut saved_maps = state.ship_maps.iter();
+        while let (Some(mut map), Some(saved)) = (maps.fetch_next(), saved_maps.next()) {
+            map.position = saved.position;
+            map.zoom = saved.zoom;
+        }
+
+        let mut doors = door_q.iter_many_mut(children);
+        let mut saved_doors = state.doors.iter();
+        while let (Some(mut door), Some(saved)) = (doors.fetch_next(), saved_doors.next()) {
+            door.open = saved.open;
+        }
+    }
+}
+
+fn save_ship_on_exit(mut exit_r: EventReader<AppExit>, mut save_w: EventWriter<SaveShip>) {
+    if exit_r.read().next().is_some() {
+        save_w.send(SaveShip);
+    }
+}
+
+/// Components read from a module to save it.
+pub type ModuleSaveData<'a> = (
+    &'a ShipModule,
+    &'a ShipModuleTransform,
+    Option<&'a ModuleAtmosphere>,
+    Option<&'a ModuleVent>,
+    Option<&'a Children>,
+);
+
+/// Everything needed to collect a [ShipSave].
+#[derive(SystemParam)]
+pub struct ShipSaveQuery<'w, 's> {
+    modules: Res<'w, ShipModuleTypes>,
+    module_q: Query<'w, 's, ModuleSaveData<'static>>,
+    tank_q: Query<'w, 's, &'static TankAtmosphere, With<Tank>>,
+    map_q: Query<'w, 's, &'static ShipMap>,
+    door_q: Query<'w, 's, &'static Door>,
+    player_q: Query<
+        'w,
+        's,
+        (
+            &'static Player,
+            &'static PlayerSession,
+            &'static Position,
+            &'static PlayerVitality,
+        ),
+    >,
+}
+
+impl ShipSaveQuery<'_, '_> {
+    /// Collects the current state of the ship into a [ShipSave].
+    pub fn collect(&self) -> ShipSave {
+        let modules = self
+            .module_q
+            .iter()
+            .filter_map(|(module, &transform, atmosphere, vent, children)| {
+                let Some(module_type) = self.modules.name(module.module_type_id) else {
+                    error!("module type {:?} has no name", module.module_type_id);
+                    return None;
+                };
+
+                let children = children.map(|children| &children[..]).unwrap_or(&[]);
+
+                Some(ShipModuleSave {
+                    module_type: module_type.to_string(),
+                    transform,
+                    state: SavedModuleState {
+                        atmosphere: atmosphere.map(|atmosphere| SavedAtmosphere {
+                            level: atmosphere.level,
+                            breached: atmosphere.breached,
+                        }),
+                        vent_open: vent.map(|vent| vent.open),
+                        tanks: self
+                            .tank_q
+                            .iter_many(children)
+                            .map(|tank| SavedTank {
+                                level: tank.level,
+                                enabled: tank.enabled,
+                            })
+                            .collect(),
+                        ship_maps: self
+                            .map_q
+                            .iter_many(children)
+                            .map(|map| SavedShipMap {
+                                position: map.position,
+                                zoom: map.zoom,
+                            })
+                            .collect(),
+                        doors: self
+                            .door_q
+                            .iter_many(children)
+                            .map(|door| SavedDoor { open: door.open })
+                            .collect(),
+                    },
+                })
+            })
+            .collect();
+
+        let players = self
+            .player_q
+            .iter()
+            .map(
+                |(player, session, &Position(position), &vitality)| PlayerSave {
+                    username: player.username.clone(),
+                    resume_token: format!("{:032x}", session.resume_token().0),
+                    position,
+                    vitality,
+                },
+            )
+            .collect();
+
+        ShipSave {
+            version: SHIP_SAVE_VERSION,
+            modules,
+            players,
+        }
+    }
+}
+
+fn save_ship(
+    mut save_r: EventReader<SaveShip>,
+    server_config: Res<crate::ServerConfig>,
+    save_disabled: Option<Res<ShipSaveDisabled>>,
+    save_q: ShipSaveQuery,
+) {
+    if save_r.read().count() == 0 {
+        return;
+    }
+
+    let path = &server_config.save_path;
+
+    if save_disabled.is_some() {
+        warn!(
+            "not saving ship to {:?} because the ship save couldn't be loaded",
+            path
+        );
+        return;
+    }
+
+    match write_ship_save(path, &save_q.collect()) {
+        Ok(()) => info!("saved ship to {:?}", path),
+        Err(err) => error!("couldn't save ship to {:?}: {}", path, err),
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    modules::{
+        grid::{ModuleRotation, ShipModuleGrid, ShipModuleTransform},
+        module_types::{spawn_ship_module, ShipModuleTypes},
+    },
+    save::{LoadShip, SavedAtmosphere, SavedModuleState, SavedTank, ShipLoadedFromSave},
+};
+
+pub fn build(app: &mut App) {
+    app.add_systems(
+        PreStartup,
+        load_scenario
+            .after(LoadShip)
+            .run_if(not(resource_exists::<ShipLoadedFromSave>)),
+    );
+}
+
+/// The starting layout of a new ship.
+///
+/// Read from `assets/scenarios/<name>.ron` where the name is the `scenario` config option.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub modules: Vec<ScenarioModule>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioModule {
+    /// name of the module type
+    pub module_type: String,
+    pub translation: IVec2,
+    #[serde(default)]
+    pub rotation: ModuleRotation,
+    /// initial atmosphere level of the module, full if not set
+    #[serde(default)]
+    pub atmosphere: Option<f32>,
+    /// initial state of the module's tanks in the order they are spawned,
+    /// tanks that aren't listed are full and disabled
+    #[serde(default)]
+    pub tanks: Vec<ScenarioTank>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioTank {
+    pub level: f32,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+fn scenario_path(name: &str) -> PathBuf {
+    PathBuf::from(crate::ASSET_PATH)
+        .join("scenarios")
+        .join(name)
+        .with_extension("ron")
+}
+
+fn load_scenario(
+    mut commands: Commands,
+    server_config: Res<crate::ServerConfig>,
+    modules: Res<ShipModuleTypes>,
+    mut grid: ResMut<ShipModuleGrid>,
+) {
+    let path = scenario_path(&server_config.scenario);
+
+    let scenario: Scenario = match std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|file| ron::from_str(&file).map_err(|err| err.to_string()))
+    {
+        Ok(scenario) => scenario,
+        Err(err) => {
+            error!("couldn't load scenario {:?}: {}", path, err);
+            return;
+        }
+    };
+
+    for ScenarioModule {
+        module_type,
+        translation,
+        rotation,
+        atmosphere,
+        tanks,
+    } in scenario.modules
+    {
+        let Some(module_type_id) = modules.find(&module_type) else {
+            error!(
+                "unknown module type \"{}\" in scenario {:?}",
+                module_type, path
+            );
+            continue;
+        };
+
+        let Some(module_entity) = spawn_ship_module(
+            &mut commands,
+            modules.as_ref(),
+            grid.as_mut(),
+            module_type_id,
+            ShipModuleTransform {
+                translation,
+                rotation,
+            },
+        ) else {
+            continue;
+        };
+
+        // initial state is applied the same way as a save once the module is initialized
+        commands.entity(module_entity).insert(SavedModuleState {
+            atmosphere: atmosphere.map(|level| SavedAtmosphere {
+                level,
+                breached: false,
+            }),
+            vent_open: None,
+            tanks: tanks
+                .into_iter()
+                .map(|ScenarioTank { level, enabled }| SavedTank { level, enabled })
+                .collect(),
+            ship_maps: Vec::new(),
+            doors: Vec::new(),
+        });
+    }
+
+    info!("started scenario {:?}", path);
+}